@@ -13,20 +13,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .init();
 
     let mut config_service = ConfigServiceBuilder::default().build().await;
-    let config =
-        config_service.get_config("hongwen.properties".to_string(), "LOVE".to_string(), 3000);
+    let config = config_service
+        .get_config("hongwen.properties".to_string(), "LOVE".to_string(), 3000)
+        .await;
     match config {
         Ok(config) => tracing::info!("get the config {}", config),
         Err(err) => tracing::error!("get the config {:?}", err),
     }
 
-    let _listen = config_service.add_listener(
-        "hongwen.properties".to_string(),
-        "LOVE".to_string(),
-        Box::new(|config_resp| {
-            tracing::info!("listen the config={:?}", config_resp);
-        }),
-    );
+    let _listen = config_service
+        .add_listener(
+            "hongwen.properties".to_string(),
+            "LOVE".to_string(),
+            Box::new(|config_resp| {
+                tracing::info!("listen the config={:?}", config_resp);
+            }),
+        )
+        .await;
     match _listen {
         Ok(_) => tracing::info!("listening the config"),
         Err(err) => tracing::error!("listen config error {:?}", err),
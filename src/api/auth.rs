@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+
+/// Auth plugin of the request pipeline, consulted by the connection before each request.
+/// The built-in username/password and access_key/secret_key schemes are plugins too,
+/// the custom ones are chained after them by [`crate::api::client_config::ClientConfig::auth_plugin`].
+#[async_trait::async_trait]
+pub trait AuthPlugin: Send + Sync {
+    /// Login before the first connecting, server_addrs are the http addresses of servers,
+    /// e.g. 127.0.0.1:8848. Return true if succeeded.
+    async fn login(&self, server_addrs: &[String]) -> bool;
+
+    /// Called periodically, e.g. login again before the token expires.
+    async fn refresh(&self, _server_addrs: &[String]) {}
+
+    /// The headers attached to the request which accesses the resource.
+    fn headers_for(&self, resource: &RequestResource) -> HashMap<String, String>;
+}
+
+impl std::fmt::Debug for dyn AuthPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AuthPlugin")
+    }
+}
+
+/// The resource accessed by request.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RequestResource {
+    /// Not access any resource, e.g. ServerCheckRequest.
+    None,
+    /// Config under tenant and group, empty if accesses more than one.
+    Config { tenant: String, group: String },
+    /// Service of naming.
+    Naming {
+        namespace: String,
+        group_name: String,
+        service_name: String,
+    },
+}
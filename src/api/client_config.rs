@@ -1,15 +1,56 @@
+use crate::api::auth::AuthPlugin;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Configures settings for Client.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ClientConfig {
-    /// server_addr like 127.0.0.1:9848
+    /// server_addr like 127.0.0.1:9848, or comma-separated 127.0.0.1:9848,127.0.0.2:9848
     pub(crate) server_addr: String,
+    /// address server like 127.0.0.1:8080, the server list is fetched from it
+    pub(crate) endpoint: Option<String>,
     pub(crate) namespace: String,
     /// app_name
     pub(crate) app_name: Option<String>,
     /// metadata
     pub(crate) labels: HashMap<String, String>,
+    /// config local cache dir, snapshot and failover are under it, default ~/nacos/config
+    pub(crate) config_cache_dir: String,
+    /// username of auth, login with it if set
+    pub(crate) username: Option<String>,
+    /// password of auth
+    pub(crate) password: Option<String>,
+    /// access_key of cloud deployments, sign requests with it if set
+    pub(crate) access_key: Option<String>,
+    /// secret_key of cloud deployments
+    pub(crate) secret_key: Option<String>,
+    /// custom auth plugins, chained after the built-in ones
+    pub(crate) auth_plugins: Vec<Arc<dyn AuthPlugin>>,
+    /// connect over TLS if set
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<TlsConfig>,
+}
+
+/// The password and secret_key are redacted, so that it is safe to log.
+impl std::fmt::Debug for ClientConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("ClientConfig");
+        debug
+            .field("server_addr", &self.server_addr)
+            .field("endpoint", &self.endpoint)
+            .field("namespace", &self.namespace)
+            .field("app_name", &self.app_name)
+            .field("labels", &self.labels)
+            .field("config_cache_dir", &self.config_cache_dir)
+            .field("username", &self.username)
+            .field("password", &redacted(&self.password))
+            .field("access_key", &self.access_key)
+            .field("secret_key", &redacted(&self.secret_key))
+            .field("auth_plugins", &self.auth_plugins);
+        #[cfg(feature = "tls")]
+        debug.field("tls", &self.tls);
+        debug.finish()
+    }
 }
 
 impl ClientConfig {
@@ -17,10 +58,19 @@ impl ClientConfig {
     pub fn new() -> Self {
         ClientConfig {
             server_addr: String::from(crate::api::constants::DEFAULT_SERVER_ADDR),
+            endpoint: None,
             /// public is "", Should define a more meaningful namespace
             namespace: String::from(""),
             app_name: None,
             labels: HashMap::default(),
+            config_cache_dir: default_config_cache_dir(),
+            username: None,
+            password: None,
+            access_key: None,
+            secret_key: None,
+            auth_plugins: Vec::new(),
+            #[cfg(feature = "tls")]
+            tls: None,
         }
     }
 
@@ -30,6 +80,26 @@ impl ClientConfig {
         self
     }
 
+    /// Sets the server addrs of a cluster, joined as comma-separated server_addr.
+    pub fn server_addrs<T: Into<String>>(
+        mut self,
+        server_addrs: impl IntoIterator<Item = T>,
+    ) -> Self {
+        self.server_addr = server_addrs
+            .into_iter()
+            .map(Into::into)
+            .collect::<Vec<String>>()
+            .join(",");
+        self
+    }
+
+    /// Sets the endpoint of address server, which serves the server list at `/nacos/serverlist`,
+    /// the server_addr is used until the server list fetched.
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
     /// Sets the namespace.
     pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
         self.namespace = namespace.into();
@@ -50,4 +120,212 @@ impl ClientConfig {
         self.labels.extend(labels.into_iter());
         self
     }
+
+    /// Sets the config local cache dir, snapshot and failover are under it.
+    pub fn config_cache_dir(mut self, config_cache_dir: impl Into<String>) -> Self {
+        self.config_cache_dir = config_cache_dir.into();
+        self
+    }
+
+    /// Sets the username of auth, the client logins with username and password
+    /// and attaches the accessToken to requests, for the auth-enabled server.
+    pub fn username(mut self, username: impl Into<String>) -> Self {
+        self.username = Some(username.into());
+        self
+    }
+
+    /// Sets the password of auth.
+    pub fn password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Sets the access_key, the client signs requests with access_key and secret_key,
+    /// for the managed server requires AK/SK signed requests.
+    pub fn access_key(mut self, access_key: impl Into<String>) -> Self {
+        self.access_key = Some(access_key.into());
+        self
+    }
+
+    /// Sets the secret_key.
+    pub fn secret_key(mut self, secret_key: impl Into<String>) -> Self {
+        self.secret_key = Some(secret_key.into());
+        self
+    }
+
+    /// Adds a custom auth plugin, consulted before each request after the built-in
+    /// username/password and access_key/secret_key ones, call it again to chain more.
+    pub fn auth_plugin(mut self, auth_plugin: impl AuthPlugin + 'static) -> Self {
+        self.auth_plugins.push(Arc::new(auth_plugin));
+        self
+    }
+
+    /// Sets the TLS settings, the grpc connections are secured with it.
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// The server addrs split from server_addr, DEFAULT_SERVER_ADDR if empty.
+    pub(crate) fn server_list(&self) -> Vec<String> {
+        let servers: Vec<String> = self
+            .server_addr
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect();
+        if servers.is_empty() {
+            vec![String::from(crate::api::constants::DEFAULT_SERVER_ADDR)]
+        } else {
+            servers
+        }
+    }
+}
+
+/// TLS settings of grpc connection, the default roots are used if no CA cert given.
+#[cfg(feature = "tls")]
+#[derive(Clone, Default)]
+pub struct TlsConfig {
+    /// PEM encoded CA certs to verify the server
+    pub(crate) ca_cert: Option<Vec<u8>>,
+    /// PEM encoded client cert chain and private key, for mTLS
+    pub(crate) client_cert: Option<(Vec<u8>, Vec<u8>)>,
+    /// the name to verify the server cert with, instead of the host of server_addr
+    pub(crate) server_name: Option<String>,
+}
+
+/// The client private key is redacted, the certs are public.
+#[cfg(feature = "tls")]
+impl std::fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field(
+                "ca_cert",
+                &self.ca_cert.as_ref().map(|c| String::from_utf8_lossy(c)),
+            )
+            .field(
+                "client_cert",
+                &self
+                    .client_cert
+                    .as_ref()
+                    .map(|(cert, _)| (String::from_utf8_lossy(cert), REDACTED)),
+            )
+            .field("server_name", &self.server_name)
+            .finish()
+    }
+}
+
+#[cfg(feature = "tls")]
+impl TlsConfig {
+    /// Creates a new `TlsConfig`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the PEM encoded CA bundle, e.g. the self-signed one.
+    pub fn ca_cert(mut self, ca_cert: impl Into<Vec<u8>>) -> Self {
+        self.ca_cert = Some(ca_cert.into());
+        self
+    }
+
+    /// Sets the PEM encoded client cert chain and private key, for mTLS.
+    pub fn client_cert(
+        mut self,
+        cert: impl Into<Vec<u8>>,
+        private_key: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.client_cert = Some((cert.into(), private_key.into()));
+        self
+    }
+
+    /// Sets the server name override, the server cert is verified with it.
+    pub fn server_name(mut self, server_name: impl Into<String>) -> Self {
+        self.server_name = Some(server_name.into());
+        self
+    }
+
+    /// The credentials of grpc channel.
+    pub(crate) fn channel_credentials(&self) -> grpcio::ChannelCredentials {
+        let mut builder = grpcio::ChannelCredentialsBuilder::new();
+        if let Some(ca_cert) = &self.ca_cert {
+            builder = builder.root_cert(ca_cert.clone());
+        }
+        if let Some((cert, private_key)) = &self.client_cert {
+            builder = builder.cert(cert.clone(), private_key.clone());
+        }
+        builder.build()
+    }
+}
+
+const REDACTED: &str = "***";
+
+/// Some("***") if set, so that it is known whether set or not.
+fn redacted(secret: &Option<String>) -> Option<&'static str> {
+    secret.as_ref().map(|_| REDACTED)
+}
+
+/// ~/nacos/config
+fn default_config_cache_dir() -> String {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    std::path::Path::new(home.as_str())
+        .join("nacos")
+        .join("config")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::client_config::ClientConfig;
+
+    #[test]
+    fn test_debug_redacted() {
+        let client_config = ClientConfig::new()
+            .username("nacos")
+            .password("the-password")
+            .access_key("ak")
+            .secret_key("the-secret-key");
+        let debug = format!("{:?}", client_config);
+        assert!(debug.contains("nacos"));
+        assert!(debug.contains("ak"));
+        assert!(!debug.contains("the-password"));
+        assert!(!debug.contains("the-secret-key"));
+        assert!(debug.contains(r#"password: Some("***")"#));
+
+        assert!(format!("{:?}", ClientConfig::new()).contains("password: None"));
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn test_tls_config() {
+        let tls = crate::api::client_config::TlsConfig::new()
+            .ca_cert("the-ca-cert")
+            .client_cert("the-client-cert", "the-private-key")
+            .server_name("nacos.test");
+        assert_eq!(Some(b"the-ca-cert".to_vec()), tls.ca_cert);
+        assert_eq!(
+            Some((b"the-client-cert".to_vec(), b"the-private-key".to_vec())),
+            tls.client_cert
+        );
+        assert_eq!(Some("nacos.test".to_string()), tls.server_name);
+
+        assert!(ClientConfig::new().tls.is_none());
+        assert!(ClientConfig::new().tls(tls).tls.is_some());
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn test_tls_debug_redacted() {
+        let tls = crate::api::client_config::TlsConfig::new()
+            .ca_cert("the-ca-cert")
+            .client_cert("the-client-cert", "the-private-key");
+        let debug = format!("{:?}", tls);
+        assert!(debug.contains("the-ca-cert"));
+        assert!(debug.contains("the-client-cert"));
+        assert!(!debug.contains("the-private-key"));
+    }
 }
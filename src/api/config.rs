@@ -1,23 +1,175 @@
 use crate::api::{client_config, error};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::Arc;
 
 pub(crate) type ConfigChangeListener = dyn Fn(ConfigResponse) + Send + Sync;
 
+pub(crate) type ConfigChangeEventListener = dyn Fn(ConfigChangeEvent) + Send + Sync;
+
+pub(crate) type TypedConfigListener<T> = dyn Fn(error::Result<T>) + Send + Sync;
+
+/// Async api of config service, use [`BlockingConfigService`] for non-async callers.
+/// The typed api is in [`ConfigServiceExt`], so that `dyn ConfigService` is usable.
+#[async_trait::async_trait]
 pub trait ConfigService {
     /// Get config, return the content.
-    fn get_config(
+    /// Return [`error::Error::Timeout`] if not responded within timeout_ms.
+    async fn get_config(
         &mut self,
         data_id: String,
         group: String,
         timeout_ms: u64,
     ) -> error::Result<String>;
 
-    /// Listen the config change.
-    fn add_listener(
+    /// Get config, return the content with its content type.
+    /// Return [`error::Error::Timeout`] if not responded within timeout_ms.
+    async fn get_config_response(
+        &mut self,
+        data_id: String,
+        group: String,
+        timeout_ms: u64,
+    ) -> error::Result<ConfigResponse>;
+
+    /// Listen the config change, return the id of listener which can be used to remove it.
+    async fn add_listener(
         &mut self,
         data_id: String,
         group: String,
         listener: Box<ConfigChangeListener>,
+    ) -> error::Result<ListenerId>;
+
+    /// Listen the config change with [`ConfigChangeEvent`], which carries the old and new
+    /// content, and the changed keys of properties, yaml and json content.
+    /// Return the id of listener which can be used to remove it by `remove_listener`.
+    async fn add_change_event_listener(
+        &mut self,
+        data_id: String,
+        group: String,
+        listener: Box<ConfigChangeEventListener>,
+    ) -> error::Result<ListenerId>;
+
+    /// Watch the config change, the receiver always holds the latest config,
+    /// initialized by get the config within timeout_ms.
+    /// Use the returned id to stop watching by `remove_listener`, the sender is also dropped
+    /// on the next change once all receivers dropped, but the config is still listened.
+    async fn watch(
+        &mut self,
+        data_id: String,
+        group: String,
+        timeout_ms: u64,
+    ) -> error::Result<(ListenerId, tokio::sync::watch::Receiver<ConfigResponse>)>;
+
+    /// Remove a listener of config change, stop listen the config when no listener left.
+    async fn remove_listener(
+        &mut self,
+        data_id: String,
+        group: String,
+        listener_id: ListenerId,
+    ) -> error::Result<()>;
+
+    /// Publish config.
+    async fn publish_config(
+        &mut self,
+        data_id: String,
+        group: String,
+        content: String,
+        content_type: Option<String>,
+    ) -> error::Result<()>;
+
+    /// Cas publish config with cas_md5 (prev content's md5).
+    async fn publish_config_cas(
+        &mut self,
+        data_id: String,
+        group: String,
+        content: String,
+        content_type: Option<String>,
+        cas_md5: String,
+    ) -> error::Result<()>;
+
+    /// Beta publish config to beta_ips (e.g. "127.0.0.1,127.0.0.2").
+    async fn publish_config_beta(
+        &mut self,
+        data_id: String,
+        group: String,
+        content: String,
+        content_type: Option<String>,
+        beta_ips: String,
+    ) -> error::Result<()>;
+
+    /// Publish config with params (e.g. tag, betaIps), cas publish when cas_md5 is Some.
+    async fn publish_config_param(
+        &mut self,
+        data_id: String,
+        group: String,
+        content: String,
+        content_type: Option<String>,
+        cas_md5: Option<String>,
+        params: HashMap<String, String>,
     ) -> error::Result<()>;
+
+    /// Remove config.
+    async fn remove_config(&mut self, data_id: String, group: String) -> error::Result<()>;
+}
+
+/// Typed api of config service, implemented for every [`ConfigService`].
+#[async_trait::async_trait]
+pub trait ConfigServiceExt: ConfigService {
+    /// Get config and deserialize into T, the parser is picked by the content type
+    /// (json, yaml, toml, properties), or by the extension of data_id if content type is text.
+    /// Return [`error::Error::ConfigParse`] if failed to deserialize.
+    async fn get_config_as<T: DeserializeOwned>(
+        &mut self,
+        data_id: String,
+        group: String,
+        timeout_ms: u64,
+    ) -> error::Result<T>;
+
+    /// Listen the config change, the content is deserialized into T like `get_config_as`.
+    /// Return the id of listener which can be used to remove it by `remove_listener`.
+    async fn add_typed_listener<T: DeserializeOwned + 'static>(
+        &mut self,
+        data_id: String,
+        group: String,
+        listener: Box<TypedConfigListener<T>>,
+    ) -> error::Result<ListenerId>;
+}
+
+#[async_trait::async_trait]
+impl<S: ConfigService + Send + ?Sized> ConfigServiceExt for S {
+    async fn get_config_as<T: DeserializeOwned>(
+        &mut self,
+        data_id: String,
+        group: String,
+        timeout_ms: u64,
+    ) -> error::Result<T> {
+        let config_resp = self.get_config_response(data_id, group, timeout_ms).await?;
+        crate::config::typed_config::deserialize_config(&config_resp)
+    }
+
+    async fn add_typed_listener<T: DeserializeOwned + 'static>(
+        &mut self,
+        data_id: String,
+        group: String,
+        listener: Box<TypedConfigListener<T>>,
+    ) -> error::Result<ListenerId> {
+        let listener = Box::new(move |config_resp: ConfigResponse| {
+            (listener)(crate::config::typed_config::deserialize_config(
+                &config_resp,
+            ))
+        });
+        self.add_listener(data_id, group, listener).await
+    }
+}
+
+/// The id of a config change listener, returned by `add_listener`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(pub(crate) u64);
+
+impl std::fmt::Display for ListenerId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ListenerId({})", self.0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -95,63 +247,485 @@ impl ConfigResponse {
     }
 }
 
+/// The change type of config item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyChangeType {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// A changed key of config content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChangeItem {
+    /// Key, nested key of yaml and json is flattened, e.g. a.b[0].c
+    key: String,
+    /// None if Added
+    old_value: Option<String>,
+    /// None if Deleted
+    new_value: Option<String>,
+    change_type: PropertyChangeType,
+}
+
+impl ConfigChangeItem {
+    pub fn new(
+        key: String,
+        old_value: Option<String>,
+        new_value: Option<String>,
+        change_type: PropertyChangeType,
+    ) -> Self {
+        ConfigChangeItem {
+            key,
+            old_value,
+            new_value,
+            change_type,
+        }
+    }
+
+    pub fn get_key(&self) -> &String {
+        &self.key
+    }
+    pub fn get_old_value(&self) -> Option<&String> {
+        self.old_value.as_ref()
+    }
+    pub fn get_new_value(&self) -> Option<&String> {
+        self.new_value.as_ref()
+    }
+    pub fn get_change_type(&self) -> PropertyChangeType {
+        self.change_type
+    }
+}
+
+/// The event of config changed, with the key-level diffs.
+#[derive(Debug, Clone)]
+pub struct ConfigChangeEvent {
+    /// Namespace/Tenant
+    namespace: String,
+    /// DataId
+    data_id: String,
+    /// Group
+    group: String,
+    /// Content's Type; e.g. json,properties,xml,html,text,yaml
+    content_type: String,
+    old_content: String,
+    new_content: String,
+    /// Empty if the content_type is not properties, yaml or json.
+    change_items: Vec<ConfigChangeItem>,
+}
+
+impl ConfigChangeEvent {
+    pub fn new(
+        data_id: String,
+        group: String,
+        namespace: String,
+        content_type: String,
+        old_content: String,
+        new_content: String,
+        change_items: Vec<ConfigChangeItem>,
+    ) -> Self {
+        ConfigChangeEvent {
+            namespace,
+            data_id,
+            group,
+            content_type,
+            old_content,
+            new_content,
+            change_items,
+        }
+    }
+
+    pub fn get_namespace(&self) -> &String {
+        &self.namespace
+    }
+    pub fn get_data_id(&self) -> &String {
+        &self.data_id
+    }
+    pub fn get_group(&self) -> &String {
+        &self.group
+    }
+    pub fn get_content_type(&self) -> &String {
+        &self.content_type
+    }
+    pub fn get_old_content(&self) -> &String {
+        &self.old_content
+    }
+    pub fn get_new_content(&self) -> &String {
+        &self.new_content
+    }
+    pub fn get_change_items(&self) -> &Vec<ConfigChangeItem> {
+        &self.change_items
+    }
+}
+
+/// The config passing through the [`ConfigFilter`] chain.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFilterData {
+    pub data_id: String,
+    pub group: String,
+    pub namespace: String,
+    pub content: String,
+    /// the data key encrypted by KMS, which the content was encrypted with.
+    pub encrypted_data_key: Option<String>,
+}
+
+/// Filter of config, applied to the config to publish, and to the config from server
+/// on query and change notify, e.g. encrypt and decrypt the content.
+pub trait ConfigFilter: Send + Sync {
+    /// Filter the config before publishing it.
+    fn filter_publish(&self, _config: &mut ConfigFilterData) -> error::Result<()> {
+        Ok(())
+    }
+
+    /// Filter the config queried from server.
+    fn filter_query(&self, _config: &mut ConfigFilterData) -> error::Result<()> {
+        Ok(())
+    }
+}
+
+/// Key management service, which keeps the master key and protects the data keys.
+pub trait KmsClient: Send + Sync {
+    /// Generate a data key for the config, return the plain one and the encrypted one.
+    fn generate_data_key(&self, data_id: &str) -> error::Result<(Vec<u8>, String)>;
+
+    /// Decrypt the encrypted data key, return the plain one.
+    fn decrypt_data_key(&self, encrypted_data_key: &str) -> error::Result<Vec<u8>>;
+}
+
+/// The prefix of data_id whose content is encrypted.
+pub const CIPHER_DATA_ID_PREFIX: &str = "cipher-";
+
+/// Encrypt the content of `cipher-` prefixed data_id with AES-128 (ECB, PKCS7 padding)
+/// by a data key from KMS on publish, and decrypt it with the data key on query.
+/// The encrypted content is base64 encoded.
+pub struct AesDataKeyFilter {
+    kms_client: Arc<dyn KmsClient>,
+}
+
+impl AesDataKeyFilter {
+    pub fn new(kms_client: impl KmsClient + 'static) -> Self {
+        AesDataKeyFilter {
+            kms_client: Arc::new(kms_client),
+        }
+    }
+}
+
+impl ConfigFilter for AesDataKeyFilter {
+    fn filter_publish(&self, config: &mut ConfigFilterData) -> error::Result<()> {
+        if !config.data_id.starts_with(CIPHER_DATA_ID_PREFIX) {
+            return Ok(());
+        }
+        let (data_key, encrypted_data_key) =
+            self.kms_client.generate_data_key(config.data_id.as_str())?;
+        config.content = crate::config::filter::aes_encrypt(&data_key, config.content.as_str())?;
+        config.encrypted_data_key = Some(encrypted_data_key);
+        Ok(())
+    }
+
+    fn filter_query(&self, config: &mut ConfigFilterData) -> error::Result<()> {
+        if !config.data_id.starts_with(CIPHER_DATA_ID_PREFIX) || config.content.is_empty() {
+            return Ok(());
+        }
+        let encrypted_data_key = match &config.encrypted_data_key {
+            Some(encrypted_data_key) if !encrypted_data_key.is_empty() => encrypted_data_key,
+            _ => {
+                return Err(error::Error::ConfigFilter(format!(
+                    "no encrypted data key of {}",
+                    config.data_id
+                )))
+            }
+        };
+        let data_key = self.kms_client.decrypt_data_key(encrypted_data_key)?;
+        config.content = crate::config::filter::aes_decrypt(&data_key, config.content.as_str())?;
+        Ok(())
+    }
+}
+
 pub struct ConfigServiceBuilder {
     client_config: client_config::ClientConfig,
+    config_filters: Vec<Arc<dyn ConfigFilter>>,
 }
 
 impl Default for ConfigServiceBuilder {
     fn default() -> Self {
         ConfigServiceBuilder {
             client_config: client_config::ClientConfig::new(),
+            config_filters: Vec::new(),
         }
     }
 }
 
 impl ConfigServiceBuilder {
     pub fn new(client_config: client_config::ClientConfig) -> Self {
-        ConfigServiceBuilder { client_config }
+        ConfigServiceBuilder {
+            client_config,
+            config_filters: Vec::new(),
+        }
+    }
+
+    /// Adds a [`ConfigFilter`], call it again to chain more. The filters are applied in order
+    /// on publish, and in reverse order on query.
+    pub fn add_config_filter(mut self, config_filter: impl ConfigFilter + 'static) -> Self {
+        self.config_filters.push(Arc::new(config_filter));
+        self
     }
 
     /// Builds a new [`ConfigService`].
     pub async fn build(self) -> impl ConfigService {
-        let mut config_service = crate::config::NacosConfigService::new(self.client_config);
+        let mut config_service =
+            crate::config::NacosConfigService::new(self.client_config, self.config_filters);
         config_service.start().await;
         config_service
     }
+
+    /// Builds a new [`BlockingConfigService`], must not be called in async context.
+    pub fn build_blocking(self) -> BlockingConfigService {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("config-blocking")
+            .enable_all()
+            .build()
+            .expect("config-blocking runtime initialization failed");
+        let mut config_service =
+            crate::config::NacosConfigService::new(self.client_config, self.config_filters);
+        runtime.block_on(config_service.start());
+        BlockingConfigService {
+            runtime,
+            inner: config_service,
+        }
+    }
+}
+
+/// Blocking facade of [`ConfigService`] for non-async callers,
+/// all methods block on an internal runtime, so must not be called in async context.
+pub struct BlockingConfigService {
+    runtime: tokio::runtime::Runtime,
+    inner: crate::config::NacosConfigService,
+}
+
+impl BlockingConfigService {
+    /// Get config, return the content.
+    pub fn get_config(
+        &mut self,
+        data_id: String,
+        group: String,
+        timeout_ms: u64,
+    ) -> error::Result<String> {
+        self.runtime
+            .block_on(self.inner.get_config(data_id, group, timeout_ms))
+    }
+
+    /// Get config and deserialize into T, the parser is picked by the content type
+    /// (json, yaml, toml, properties), or by the extension of data_id if content type is text.
+    pub fn get_config_as<T: DeserializeOwned>(
+        &mut self,
+        data_id: String,
+        group: String,
+        timeout_ms: u64,
+    ) -> error::Result<T> {
+        self.runtime
+            .block_on(self.inner.get_config_as(data_id, group, timeout_ms))
+    }
+
+    /// Listen the config change, return the id of listener which can be used to remove it.
+    pub fn add_listener(
+        &mut self,
+        data_id: String,
+        group: String,
+        listener: Box<ConfigChangeListener>,
+    ) -> error::Result<ListenerId> {
+        self.runtime
+            .block_on(self.inner.add_listener(data_id, group, listener))
+    }
+
+    /// Listen the config change, the content is deserialized into T like `get_config_as`.
+    pub fn add_typed_listener<T: DeserializeOwned + 'static>(
+        &mut self,
+        data_id: String,
+        group: String,
+        listener: Box<TypedConfigListener<T>>,
+    ) -> error::Result<ListenerId> {
+        self.runtime
+            .block_on(self.inner.add_typed_listener(data_id, group, listener))
+    }
+
+    /// Listen the config change with [`ConfigChangeEvent`], which carries the old and new
+    /// content, and the changed keys of properties, yaml and json content.
+    pub fn add_change_event_listener(
+        &mut self,
+        data_id: String,
+        group: String,
+        listener: Box<ConfigChangeEventListener>,
+    ) -> error::Result<ListenerId> {
+        self.runtime.block_on(
+            self.inner
+                .add_change_event_listener(data_id, group, listener),
+        )
+    }
+
+    /// Watch the config change, the receiver always holds the latest config,
+    /// initialized by get the config within timeout_ms.
+    pub fn watch(
+        &mut self,
+        data_id: String,
+        group: String,
+        timeout_ms: u64,
+    ) -> error::Result<(ListenerId, tokio::sync::watch::Receiver<ConfigResponse>)> {
+        self.runtime
+            .block_on(self.inner.watch(data_id, group, timeout_ms))
+    }
+
+    /// Remove a listener of config change, stop listen the config when no listener left.
+    pub fn remove_listener(
+        &mut self,
+        data_id: String,
+        group: String,
+        listener_id: ListenerId,
+    ) -> error::Result<()> {
+        self.runtime
+            .block_on(self.inner.remove_listener(data_id, group, listener_id))
+    }
+
+    /// Publish config.
+    pub fn publish_config(
+        &mut self,
+        data_id: String,
+        group: String,
+        content: String,
+        content_type: Option<String>,
+    ) -> error::Result<()> {
+        self.runtime.block_on(
+            self.inner
+                .publish_config(data_id, group, content, content_type),
+        )
+    }
+
+    /// Cas publish config with cas_md5 (prev content's md5).
+    pub fn publish_config_cas(
+        &mut self,
+        data_id: String,
+        group: String,
+        content: String,
+        content_type: Option<String>,
+        cas_md5: String,
+    ) -> error::Result<()> {
+        self.runtime.block_on(self.inner.publish_config_cas(
+            data_id,
+            group,
+            content,
+            content_type,
+            cas_md5,
+        ))
+    }
+
+    /// Beta publish config to beta_ips (e.g. "127.0.0.1,127.0.0.2").
+    pub fn publish_config_beta(
+        &mut self,
+        data_id: String,
+        group: String,
+        content: String,
+        content_type: Option<String>,
+        beta_ips: String,
+    ) -> error::Result<()> {
+        self.runtime.block_on(self.inner.publish_config_beta(
+            data_id,
+            group,
+            content,
+            content_type,
+            beta_ips,
+        ))
+    }
+
+    /// Publish config with params (e.g. tag, betaIps), cas publish when cas_md5 is Some.
+    pub fn publish_config_param(
+        &mut self,
+        data_id: String,
+        group: String,
+        content: String,
+        content_type: Option<String>,
+        cas_md5: Option<String>,
+        params: HashMap<String, String>,
+    ) -> error::Result<()> {
+        self.runtime.block_on(self.inner.publish_config_param(
+            data_id,
+            group,
+            content,
+            content_type,
+            cas_md5,
+            params,
+        ))
+    }
+
+    /// Remove config.
+    pub fn remove_config(&mut self, data_id: String, group: String) -> error::Result<()> {
+        self.runtime
+            .block_on(self.inner.remove_config(data_id, group))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::api::config::ConfigService;
     use crate::api::config::ConfigServiceBuilder;
+    use crate::api::config::ConfigServiceExt;
     use std::time::Duration;
     use tokio::time::sleep;
 
+    /// The typed api works on `dyn ConfigService` too.
+    #[allow(dead_code)]
+    async fn get_config_as_dyn(
+        config_service: &mut (dyn ConfigService + Send),
+    ) -> crate::api::error::Result<std::collections::HashMap<String, String>> {
+        config_service
+            .get_config_as(
+                "app.properties".to_string(),
+                "DEFAULT_GROUP".to_string(),
+                3000,
+            )
+            .await
+    }
+
     // #[tokio::test]
     async fn test_api_config_service() {
         tracing_subscriber::fmt()
             .with_max_level(tracing::Level::DEBUG)
             .init();
         let mut config_service = ConfigServiceBuilder::default().build().await;
-        let config =
-            config_service.get_config("hongwen.properties".to_string(), "LOVE".to_string(), 3000);
+        let config = config_service
+            .get_config("hongwen.properties".to_string(), "LOVE".to_string(), 3000)
+            .await;
         match config {
             Ok(config) => tracing::info!("get the config {}", config),
             Err(err) => tracing::error!("get the config {:?}", err),
         }
 
-        let _listen = config_service.add_listener(
-            "hongwen.properties".to_string(),
-            "LOVE".to_string(),
-            Box::new(|config_resp| {
-                tracing::info!("listen the config {}", config_resp.get_content());
-            }),
-        );
+        let _listen = config_service
+            .add_listener(
+                "hongwen.properties".to_string(),
+                "LOVE".to_string(),
+                Box::new(|config_resp| {
+                    tracing::info!("listen the config {}", config_resp.get_content());
+                }),
+            )
+            .await;
         match _listen {
-            Ok(_) => tracing::info!("listening the config"),
+            Ok(listener_id) => {
+                tracing::info!("listening the config, {}", listener_id);
+
+                sleep(Duration::from_secs(30)).await;
+
+                let _remove = config_service
+                    .remove_listener(
+                        "hongwen.properties".to_string(),
+                        "LOVE".to_string(),
+                        listener_id,
+                    )
+                    .await;
+                match _remove {
+                    Ok(_) => tracing::info!("removed the listener {}", listener_id),
+                    Err(err) => tracing::error!("remove listener error {:?}", err),
+                }
+            }
             Err(err) => tracing::error!("listen config error {:?}", err),
         }
-
-        sleep(Duration::from_secs(30)).await;
     }
 }
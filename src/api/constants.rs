@@ -4,3 +4,12 @@ pub const DEFAULT_SERVER_ADDR: &'static str = "0.0.0.0:9848";
 
 /// label AppName
 pub const KEY_LABEL_APP_NAME: &'static str = "AppName";
+
+/// default group of naming service
+pub const DEFAULT_GROUP: &'static str = "DEFAULT_GROUP";
+
+/// default cluster of service instance
+pub const DEFAULT_CLUSTER_NAME: &'static str = "DEFAULT";
+
+/// the spliter of group name and service name, e.g. DEFAULT_GROUP@@service_name
+pub const SERVICE_INFO_SPLITER: &'static str = "@@";
@@ -27,9 +27,30 @@ pub enum Error {
     #[error("Deserialization failed: {0}")]
     Deserialization(String),
 
+    #[error("nacos server error result: {0}")]
+    ErrResult(String),
+
+    #[error("request timeout: {0}")]
+    Timeout(String),
+
+    #[error("config parse failed: {0}")]
+    ConfigParse(String),
+
+    #[error("auth failed: {0}")]
+    AuthFailed(String),
+
+    #[error("config filter failed: {0}")]
+    ConfigFilter(String),
+
+    #[error("no available instance: {0}")]
+    NoAvailableInstance(String),
+
     #[error("remote client shutdown failed: {0}")]
     ClientShutdown(String),
 
+    #[error("http request failed: {0}")]
+    HttpRequest(#[from] reqwest::Error),
+
     #[error("grpcio conn failed: {0}")]
     GrpcioJoin(#[from] grpcio::Error),
 
@@ -39,3 +60,10 @@ pub enum Error {
     #[error("tokio oneshot receive failed: {0}")]
     TokioOneshotRecv(#[from] tokio::sync::oneshot::error::RecvError),
 }
+
+/// Used by the deserializer of config content.
+impl serde::de::Error for Error {
+    fn custom<M: std::fmt::Display>(msg: M) -> Self {
+        Error::ConfigParse(msg.to_string())
+    }
+}
@@ -1,6 +1,10 @@
+pub mod auth;
 pub mod client_config;
 pub mod constants;
 pub mod error;
 
 #[cfg(feature = "config")]
 pub mod config;
+
+#[cfg(feature = "naming")]
+pub mod naming;
@@ -0,0 +1,512 @@
+use crate::api::{client_config, error};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub(crate) type InstancesChangeListener = dyn Fn(Vec<ServiceInstance>) + Send + Sync;
+
+pub(crate) type InstancesChangeEventListener = dyn Fn(InstancesChangeEvent) + Send + Sync;
+
+/// Async api of naming service.
+#[async_trait::async_trait]
+pub trait NamingService {
+    /// Register an instance to service, group_name is DEFAULT_GROUP if None.
+    async fn register_instance(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        service_instance: ServiceInstance,
+    ) -> error::Result<()>;
+
+    /// Deregister an instance from service, group_name is DEFAULT_GROUP if None.
+    async fn deregister_instance(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        service_instance: ServiceInstance,
+    ) -> error::Result<()>;
+
+    /// Get all instances of service in the clusters, all clusters if clusters is empty.
+    async fn get_all_instances(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+    ) -> error::Result<Vec<ServiceInstance>>;
+
+    /// Select the enabled instances of service in the clusters,
+    /// only the healthy ones if healthy_only is true.
+    async fn select_instances(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+        healthy_only: bool,
+    ) -> error::Result<Vec<ServiceInstance>>;
+
+    /// Select one healthy instance of service in the clusters, random by weight.
+    async fn select_one_healthy_instance(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+    ) -> error::Result<ServiceInstance>;
+
+    /// Select one healthy instance of service in the clusters by the selector,
+    /// e.g. [`WeightedRandomSelector`], [`RoundRobinSelector`], [`ConsistentHashSelector`].
+    async fn select_instance(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+        selector: &dyn Selector,
+    ) -> error::Result<ServiceInstance>;
+
+    /// Subscribe the instances change of service, the listener receives the newest
+    /// instances in the clusters, all clusters if clusters is empty.
+    /// Return the id of subscription which can be used to `unsubscribe`.
+    async fn subscribe(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+        listener: Box<InstancesChangeListener>,
+    ) -> error::Result<SubscriptionId>;
+
+    /// Subscribe the instances change of service with [`InstancesChangeEvent`], which carries
+    /// the added, removed and modified instances in the clusters, all clusters if clusters is empty.
+    /// Return the id of subscription which can be used to `unsubscribe`.
+    async fn subscribe_change_event(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+        listener: Box<InstancesChangeEventListener>,
+    ) -> error::Result<SubscriptionId>;
+
+    /// Unsubscribe the instances change of service, stop subscribe when no listener left.
+    async fn unsubscribe(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        subscription_id: SubscriptionId,
+    ) -> error::Result<()>;
+}
+
+/// Load balancer of naming, select one instance from the service.
+pub trait Selector: Send + Sync {
+    /// Select one from the hosts of service_info, which are the healthy and enabled
+    /// instances in the clusters. Return None if no one suitable.
+    fn select(&self, service_info: &ServiceInfo) -> Option<ServiceInstance>;
+}
+
+/// Select random by the weight of instances.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WeightedRandomSelector;
+
+impl Selector for WeightedRandomSelector {
+    fn select(&self, service_info: &ServiceInfo) -> Option<ServiceInstance> {
+        let hosts = &service_info.hosts;
+        let total_weight: f64 = hosts.iter().map(|instance| instance.weight).sum();
+        if hosts.is_empty() || total_weight <= 0.0 {
+            return None;
+        }
+        let mut random = rand::random::<f64>() * total_weight;
+        hosts
+            .iter()
+            .find(|instance| {
+                random -= instance.weight;
+                random < 0.0
+            })
+            .or_else(|| hosts.last())
+            .cloned()
+    }
+}
+
+/// Select in turn, the instances are ordered by ip:port.
+#[derive(Debug, Default)]
+pub struct RoundRobinSelector {
+    next: AtomicUsize,
+}
+
+impl RoundRobinSelector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Selector for RoundRobinSelector {
+    fn select(&self, service_info: &ServiceInfo) -> Option<ServiceInstance> {
+        if service_info.hosts.is_empty() {
+            return None;
+        }
+        let mut hosts: Vec<&ServiceInstance> = service_info.hosts.iter().collect();
+        hosts.sort_by_key(|instance| instance.ip_and_port());
+        let idx = self.next.fetch_add(1, Ordering::Relaxed) % hosts.len();
+        Some(hosts[idx].clone())
+    }
+}
+
+/// Select by the hash of key, the same key selects the same instance as long as it exists,
+/// only the keys on the removed instance move when instances change.
+/// Weighted rendezvous hashing, learn from https://en.wikipedia.org/wiki/Rendezvous_hashing
+#[derive(Debug, Clone)]
+pub struct ConsistentHashSelector {
+    key: String,
+}
+
+impl ConsistentHashSelector {
+    pub fn new(key: impl Into<String>) -> Self {
+        ConsistentHashSelector { key: key.into() }
+    }
+
+    /// The score of instance for the key, the highest one is selected.
+    fn score(&self, instance: &ServiceInstance) -> f64 {
+        let hash = fnv1a_64(format!("{}@{}", self.key, instance.ip_and_port()).as_bytes());
+        // map the hash into (0, 1)
+        let unit = ((hash >> 11) as f64 + 0.5) / (1u64 << 53) as f64;
+        instance.weight / -unit.ln()
+    }
+}
+
+/// 64-bit FNV-1a, stable across builds unlike the std DefaultHasher,
+/// so that every client selects the same instance for a key.
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+impl Selector for ConsistentHashSelector {
+    fn select(&self, service_info: &ServiceInfo) -> Option<ServiceInstance> {
+        service_info
+            .hosts
+            .iter()
+            .filter(|instance| instance.weight > 0.0)
+            .map(|instance| (self.score(instance), instance))
+            .max_by(|(a, _), (b, _)| a.total_cmp(b))
+            .map(|(_, instance)| instance.clone())
+    }
+}
+
+/// The id of a service subscription, returned by `subscribe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(pub(crate) u64);
+
+impl std::fmt::Display for SubscriptionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SubscriptionId({})", self.0)
+    }
+}
+
+/// Instance of service, learn from com.alibaba.nacos.api.naming.pojo.Instance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ServiceInstance {
+    /// Unique id of this instance, generated by server.
+    pub instance_id: Option<String>,
+    /// Instance ip
+    pub ip: String,
+    /// Instance port
+    pub port: i32,
+    /// Instance weight
+    pub weight: f64,
+    /// Instance health status
+    pub healthy: bool,
+    /// If instance is enabled to accept request
+    pub enabled: bool,
+    /// If instance is ephemeral, which is removed once the connection closed.
+    pub ephemeral: bool,
+    /// Cluster information of instance
+    pub cluster_name: String,
+    /// Service information of instance, with group, e.g. DEFAULT_GROUP@@service_name
+    pub service_name: Option<String>,
+    /// User extended attributes
+    pub metadata: HashMap<String, String>,
+}
+
+impl Default for ServiceInstance {
+    fn default() -> Self {
+        ServiceInstance {
+            instance_id: None,
+            ip: String::default(),
+            port: 0,
+            weight: 1.0,
+            healthy: true,
+            enabled: true,
+            ephemeral: true,
+            cluster_name: String::from(crate::api::constants::DEFAULT_CLUSTER_NAME),
+            service_name: None,
+            metadata: HashMap::default(),
+        }
+    }
+}
+
+impl ServiceInstance {
+    /// Creates a new `ServiceInstance` with ip and port.
+    pub fn new(ip: impl Into<String>, port: i32) -> Self {
+        ServiceInstance {
+            ip: ip.into(),
+            port,
+            ..Default::default()
+        }
+    }
+
+    /// ip:port, identify an instance in a service.
+    pub fn ip_and_port(&self) -> String {
+        format!("{}:{}", self.ip, self.port)
+    }
+}
+
+/// Service with its instances, learn from com.alibaba.nacos.api.naming.pojo.ServiceInfo
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ServiceInfo {
+    /// Service name
+    pub name: String,
+    /// Group name
+    pub group_name: String,
+    /// Clusters joined by comma
+    pub clusters: String,
+    /// Cache time in milliseconds
+    pub cache_millis: i64,
+    /// Instances of service
+    pub hosts: Vec<ServiceInstance>,
+    /// Last refresh time in milliseconds
+    pub last_ref_time: i64,
+    /// Checksum of instances
+    pub checksum: String,
+    /// If all the instances returned
+    #[serde(rename = "allIPs")]
+    pub all_ips: bool,
+    /// If the healthy protection threshold is reached
+    pub reach_protection_threshold: bool,
+}
+
+impl ServiceInfo {
+    /// group_name@@name, the name from server may be grouped already.
+    pub fn get_grouped_service_name(&self) -> String {
+        if self
+            .name
+            .contains(crate::api::constants::SERVICE_INFO_SPLITER)
+        {
+            self.name.clone()
+        } else {
+            grouped_service_name(&self.name, &self.group_name)
+        }
+    }
+
+    /// Instances in the clusters, all instances if clusters is empty.
+    pub fn hosts_of_clusters(&self, clusters: &[String]) -> Vec<ServiceInstance> {
+        self.hosts
+            .iter()
+            .filter(|instance| clusters.is_empty() || clusters.contains(&instance.cluster_name))
+            .cloned()
+            .collect()
+    }
+}
+
+/// The instances change of service, the instances are identified by ip:port.
+#[derive(Debug, Clone)]
+pub struct InstancesChangeEvent {
+    /// Service name
+    service_name: String,
+    /// Group name
+    group_name: String,
+    /// The newest instances
+    instances: Vec<ServiceInstance>,
+    /// The new instances
+    added_instances: Vec<ServiceInstance>,
+    /// The instances no longer exist
+    removed_instances: Vec<ServiceInstance>,
+    /// The instances with changed weight, health, metadata and so on, the new ones.
+    modified_instances: Vec<ServiceInstance>,
+}
+
+impl InstancesChangeEvent {
+    pub fn new(
+        service_name: String,
+        group_name: String,
+        instances: Vec<ServiceInstance>,
+        added_instances: Vec<ServiceInstance>,
+        removed_instances: Vec<ServiceInstance>,
+        modified_instances: Vec<ServiceInstance>,
+    ) -> Self {
+        InstancesChangeEvent {
+            service_name,
+            group_name,
+            instances,
+            added_instances,
+            removed_instances,
+            modified_instances,
+        }
+    }
+
+    pub fn get_service_name(&self) -> &String {
+        &self.service_name
+    }
+    pub fn get_group_name(&self) -> &String {
+        &self.group_name
+    }
+    pub fn get_instances(&self) -> &Vec<ServiceInstance> {
+        &self.instances
+    }
+    pub fn get_added_instances(&self) -> &Vec<ServiceInstance> {
+        &self.added_instances
+    }
+    pub fn get_removed_instances(&self) -> &Vec<ServiceInstance> {
+        &self.removed_instances
+    }
+    pub fn get_modified_instances(&self) -> &Vec<ServiceInstance> {
+        &self.modified_instances
+    }
+    /// True if no instance added, removed or modified.
+    pub fn is_empty(&self) -> bool {
+        self.added_instances.is_empty()
+            && self.removed_instances.is_empty()
+            && self.modified_instances.is_empty()
+    }
+}
+
+/// group_name@@service_name
+pub(crate) fn grouped_service_name(service_name: &str, group_name: &str) -> String {
+    format!(
+        "{}{}{}",
+        group_name,
+        crate::api::constants::SERVICE_INFO_SPLITER,
+        service_name
+    )
+}
+
+pub struct NamingServiceBuilder {
+    client_config: client_config::ClientConfig,
+}
+
+impl Default for NamingServiceBuilder {
+    fn default() -> Self {
+        NamingServiceBuilder {
+            client_config: client_config::ClientConfig::new(),
+        }
+    }
+}
+
+impl NamingServiceBuilder {
+    pub fn new(client_config: client_config::ClientConfig) -> Self {
+        NamingServiceBuilder { client_config }
+    }
+
+    /// Builds a new [`NamingService`].
+    pub async fn build(self) -> impl NamingService {
+        let mut naming_service = crate::naming::NacosNamingService::new(self.client_config);
+        naming_service.start().await;
+        naming_service
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::naming::{
+        ConsistentHashSelector, NamingService, NamingServiceBuilder, RoundRobinSelector, Selector,
+        ServiceInfo, ServiceInstance, WeightedRandomSelector,
+    };
+    use std::time::Duration;
+    use tokio::time::sleep;
+
+    #[test]
+    fn test_service_info_deserialize() {
+        let json = r#"{"name":"test-service","groupName":"DEFAULT_GROUP","clusters":"",
+            "cacheMillis":10000,"lastRefTime":1666000000000,"checksum":"","allIPs":false,
+            "reachProtectionThreshold":false,"valid":true,"hosts":[
+            {"instanceId":"1","ip":"127.0.0.1","port":8080,"weight":1.0,"healthy":true,
+             "enabled":true,"ephemeral":true,"clusterName":"DEFAULT",
+             "serviceName":"DEFAULT_GROUP@@test-service","metadata":{"k":"v"},
+             "instanceHeartBeatInterval":5000},
+            {"ip":"127.0.0.2","port":8080,"healthy":false,"clusterName":"OTHER"}]}"#;
+        let service_info: ServiceInfo = serde_json::from_str(json).unwrap();
+        assert_eq!("test-service", service_info.name);
+        assert_eq!(10000, service_info.cache_millis);
+        assert_eq!(2, service_info.hosts.len());
+        assert_eq!(
+            Some("v"),
+            service_info.hosts[0].metadata.get("k").map(|v| v.as_str())
+        );
+        assert_eq!(1.0, service_info.hosts[1].weight);
+
+        let hosts = service_info.hosts_of_clusters(&["OTHER".to_string()]);
+        assert_eq!(1, hosts.len());
+        assert_eq!("127.0.0.2:8080", hosts[0].ip_and_port());
+        assert_eq!(2, service_info.hosts_of_clusters(&[]).len());
+    }
+
+    #[test]
+    fn test_selectors() {
+        let hosts: Vec<ServiceInstance> = (1..=4)
+            .map(|i| ServiceInstance::new(format!("127.0.0.{}", i), 8080))
+            .collect();
+        let service_info = ServiceInfo {
+            hosts: hosts.clone(),
+            ..Default::default()
+        };
+        let empty = ServiceInfo::default();
+
+        assert!(WeightedRandomSelector.select(&service_info).is_some());
+        assert!(WeightedRandomSelector.select(&empty).is_none());
+
+        let round_robin = RoundRobinSelector::new();
+        let selected: Vec<String> = (0..5)
+            .map(|_| round_robin.select(&service_info).unwrap().ip_and_port())
+            .collect();
+        assert_eq!("127.0.0.1:8080", selected[0]);
+        assert_eq!("127.0.0.4:8080", selected[3]);
+        assert_eq!("127.0.0.1:8080", selected[4]);
+        assert!(round_robin.select(&empty).is_none());
+
+        let consistent_hash = ConsistentHashSelector::new("user-1");
+        let selected = consistent_hash.select(&service_info).unwrap();
+        assert_eq!(selected, consistent_hash.select(&service_info).unwrap());
+        // the key stays on its instance if another instance removed.
+        let other = hosts.iter().find(|h| **h != selected).unwrap();
+        let service_info = ServiceInfo {
+            hosts: hosts.iter().filter(|h| *h != other).cloned().collect(),
+            ..Default::default()
+        };
+        assert_eq!(selected, consistent_hash.select(&service_info).unwrap());
+        assert!(consistent_hash.select(&empty).is_none());
+    }
+
+    #[test]
+    fn test_fnv1a_64() {
+        use crate::api::naming::fnv1a_64;
+        assert_eq!(0xcbf29ce484222325, fnv1a_64(b""));
+        assert_eq!(0xaf63dc4c8601ec8c, fnv1a_64(b"a"));
+        assert_eq!(0x85944171f73967e8, fnv1a_64(b"foobar"));
+    }
+
+    // #[tokio::test]
+    async fn test_api_naming_service() {
+        tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .init();
+        let mut naming_service = NamingServiceBuilder::default().build().await;
+        let service_instance = ServiceInstance::new("127.0.0.1", 8080);
+        let register = naming_service
+            .register_instance("test-service".to_string(), None, service_instance.clone())
+            .await;
+        match register {
+            Ok(_) => tracing::info!("registered the instance"),
+            Err(err) => tracing::error!("register instance error {:?}", err),
+        }
+
+        sleep(Duration::from_secs(30)).await;
+
+        let deregister = naming_service
+            .deregister_instance("test-service".to_string(), None, service_instance)
+            .await;
+        match deregister {
+            Ok(_) => tracing::info!("deregistered the instance"),
+            Err(err) => tracing::error!("deregister instance error {:?}", err),
+        }
+    }
+}
@@ -4,23 +4,40 @@
 use futures::stream::StreamExt;
 use futures::SinkExt;
 
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use std::{error::Error, time::Duration};
 
 use crate::api::client_config::ClientConfig;
 use crate::common::remote::request::client_request::{
-    ConnectionSetupClientRequest, ServerCheckClientRequest,
+    ConnectionSetupClientRequest, HealthCheckClientRequest, ServerCheckClientRequest,
 };
 use crate::common::remote::request::Request;
-use crate::common::remote::response::Response;
+use crate::common::remote::response::server_response::ErrorResponse;
+use crate::common::remote::response::{
+    Response, TYPE_ERROR_SERVER_RESPONSE, TYPE_HEALTH_CHECK_SERVER_RESPONSE,
+};
+use crate::common::remote::security::SecurityProxy;
+use crate::common::remote::server_list::ServerListManager;
 use crate::common::util::payload_helper::PayloadInner;
 use crate::common::util::*;
 use crate::nacos_proto::v2::{BiRequestStreamClient, Payload, RequestClient};
 
+/// The clones of a connection share the same state, so that the reconnecting or resetting
+/// by one of them, e.g. the bi-stream receiver, takes effect on all of them.
 #[derive(Clone)]
 pub struct Connection {
     client_config: ClientConfig,
-    state: State,
+    server_list: ServerListManager,
+    security_proxy: SecurityProxy,
+    state: Arc<Mutex<State>>,
+    /// only one of the clones is connecting at a time.
+    connecting: Arc<tokio::sync::Mutex<()>>,
+    /// the last time of receiving anything from server.
+    last_active: Arc<Mutex<Instant>>,
+    /// the health check failed in a row.
+    health_check_failures: Arc<AtomicU32>,
 }
 
 // clippy doesn't like that the "connected" case is much larger than the
@@ -37,8 +54,9 @@ enum State {
         channel: grpcio::Channel,
         client: RequestClient,
         bi_client: BiRequestStreamClient,
-        bi_sender: Arc<Mutex<grpcio::ClientDuplexSender<Payload>>>,
-        bi_receiver: Arc<Mutex<grpcio::ClientDuplexReceiver<Payload>>>,
+        /// async mutex, since the sending and receiving are awaited while holding it.
+        bi_sender: Arc<tokio::sync::Mutex<grpcio::ClientDuplexSender<Payload>>>,
+        bi_receiver: Arc<tokio::sync::Mutex<grpcio::ClientDuplexReceiver<Payload>>>,
     },
     Disconnected(Duration),
 }
@@ -46,102 +64,336 @@ enum State {
 impl Connection {
     const BACKOFF: Duration = Duration::from_millis(500);
 
+    /// The interval of checking whether the connection is idle and needs a health check.
+    pub(crate) const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+    /// Send health check if nothing received from server for the keep alive time.
+    const KEEP_ALIVE_TIME: Duration = Duration::from_secs(5);
+    const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+    /// Reconnect after the health check failed in a row for the times.
+    const MAX_HEALTH_CHECK_FAILURES: u32 = 3;
+    /// The timeout of connecting to one server in `connect_once`.
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
     pub(crate) fn new(client_config: ClientConfig) -> Self {
+        let server_list = ServerListManager::new(&client_config);
+        let security_proxy = SecurityProxy::new(&client_config);
         Self {
             client_config,
-            state: State::Disconnected(Duration::from_secs(0)),
+            server_list,
+            security_proxy,
+            state: Arc::new(Mutex::new(State::Disconnected(Duration::from_secs(0)))),
+            connecting: Arc::new(tokio::sync::Mutex::new(())),
+            last_active: Arc::new(Mutex::new(Instant::now())),
+            health_check_failures: Arc::new(AtomicU32::new(0)),
         }
     }
 
+    fn state(&self) -> State {
+        loop {
+            let state_lock = self.state.try_lock();
+            if let Ok(mutex) = state_lock {
+                return mutex.clone();
+            }
+        }
+    }
+
+    /// Replace the state, the old connected one is dropped.
+    fn set_state(&self, state: State) {
+        if let State::Connected { .. } = state {
+            self.mark_active();
+            self.health_check_failures.store(0, Ordering::Relaxed);
+        }
+        loop {
+            let state_lock = self.state.try_lock();
+            if let Ok(mut mutex) = state_lock {
+                *mutex = state;
+                break;
+            }
+        }
+    }
+
+    /// Mark disconnected, only if the connection id is still the given one,
+    /// it may be reconnected by another clone already.
+    fn set_disconnected(&self, conn_id: &String) {
+        loop {
+            let state_lock = self.state.try_lock();
+            if let Ok(mut mutex) = state_lock {
+                if let State::Connected {
+                    conn_id: ref current,
+                    ..
+                } = *mutex
+                {
+                    if current == conn_id {
+                        self.server_list.next_server();
+                        *mutex = State::Disconnected(Self::BACKOFF);
+                    }
+                }
+                break;
+            }
+        }
+    }
+
+    /// Fetch the server list and login before the first connecting, only once.
+    async fn before_connect(&self) {
+        // fetch the server list from endpoint before the first connecting.
+        self.server_list.start().await;
+        // login before the first connecting if auth enabled.
+        self.security_proxy.start(self.server_list.clone()).await;
+    }
+
+    /// Try each server once, keep disconnected if all of them failed,
+    /// so that the caller is not blocked when the whole cluster is down.
+    /// Return true if connected.
+    pub(crate) async fn connect_once(&mut self) -> bool {
+        self.before_connect().await;
+
+        let _connecting = self.connecting.lock().await;
+        if let State::Connected { .. } = self.state() {
+            return true;
+        }
+        for _ in 0..self.server_list.servers().len() {
+            let target = self.server_list.current_server();
+            tracing::info!(to = %target, "connecting");
+            match tokio::time::timeout(Self::CONNECT_TIMEOUT, self.try_connect(target.clone()))
+                .await
+            {
+                Ok(Ok(connected)) => {
+                    tracing::debug!("connected successfully!");
+                    self.set_state(connected);
+                    return true;
+                }
+                Ok(Err(error)) => tracing::warn!(%error, to = %target, "error connecting"),
+                Err(_) => tracing::warn!(to = %target, "connecting timeout"),
+            }
+            self.server_list.next_server();
+        }
+        false
+    }
+
     pub(crate) async fn connect(&mut self) {
         const MAX_BACKOFF: Duration = Duration::from_secs(5);
 
-        while let State::Disconnected(backoff) = self.state {
+        self.before_connect().await;
+
+        let _connecting = self.connecting.lock().await;
+        while let State::Disconnected(backoff) = self.state() {
             if backoff == Duration::from_secs(0) {
-                tracing::info!(to = %self.client_config.server_addr, "connecting");
+                tracing::info!(to = %self.server_list.current_server(), "connecting");
             } else {
                 tracing::info!(reconnect_in = ?backoff, "reconnecting");
                 tokio::time::sleep(backoff).await;
             }
 
-            let try_connect = async {
-                let target = self.client_config.server_addr.clone();
-                let tenant = self.client_config.namespace.clone();
-                let labels = self.client_config.labels.clone();
-
-                let env = Arc::new(grpcio::Environment::new(2));
-                let channel = grpcio::ChannelBuilder::new(env).connect(target.as_str());
-
-                let client = RequestClient::new(channel.clone());
-
-                let req_payload =
-                    payload_helper::build_req_grpc_payload(ServerCheckClientRequest::new());
-                let resp_payload = client.request(&req_payload)?;
-                let server_check_response = payload_helper::build_server_response(resp_payload)?;
-                let conn_id = server_check_response
-                    .get_connection_id()
-                    .ok_or(crate::api::error::Error::ClientShutdown(format!(
-                        "Get connection_id failed,error_code={},message={}",
-                        server_check_response.get_error_code(),
-                        server_check_response
-                            .get_message()
-                            .or(Some(&"".to_string()))
-                            .unwrap(),
-                    )))?
-                    .to_string();
-
-                let bi_client = BiRequestStreamClient::new(channel.clone());
-                let (mut client_sender, client_receiver) = bi_client.request_bi_stream()?;
-                // send a ConnectionSetupClientRequest
-                client_sender
-                    .send((
-                        payload_helper::build_req_grpc_payload(ConnectionSetupClientRequest::new(
-                            tenant, labels,
-                        )),
-                        grpcio::WriteFlags::default(),
-                    ))
-                    .await?;
-
-                Ok::<State, Box<dyn Error + Send + Sync>>(State::Connected {
-                    target,
-                    conn_id,
-                    channel,
-                    client,
-                    bi_client,
-                    bi_sender: Arc::new(Mutex::new(client_sender)),
-                    bi_receiver: Arc::new(Mutex::new(client_receiver)),
-                })
-            };
-            self.state = match try_connect.await {
+            let state = match self.try_connect(self.server_list.current_server()).await {
                 Ok(connected) => {
                     tracing::debug!("connected successfully!");
                     connected
                 }
                 Err(error) => {
-                    tracing::warn!(%error, "error connecting");
+                    let next_server = self.server_list.next_server();
+                    tracing::warn!(%error, next = %next_server, "error connecting");
                     let backoff = std::cmp::min(backoff + Self::BACKOFF, MAX_BACKOFF);
                     State::Disconnected(backoff)
                 }
             };
+            self.set_state(state);
+        }
+    }
+
+    /// Connect to the target, check server and setup the bi-stream.
+    async fn try_connect(&self, target: String) -> Result<State, Box<dyn Error + Send + Sync>> {
+        let tenant = self.client_config.namespace.clone();
+        let labels = self.client_config.labels.clone();
+
+        let channel = self.build_channel(target.as_str());
+
+        let client = RequestClient::new(channel.clone());
+
+        let req_payload = payload_helper::build_req_grpc_payload(ServerCheckClientRequest::new());
+        let resp_payload = client.request_async(&req_payload)?.await?;
+        let conn_id = {
+            let server_check_response = payload_helper::build_server_response(resp_payload)?;
+            server_check_response
+                .get_connection_id()
+                .ok_or(crate::api::error::Error::ClientShutdown(format!(
+                    "Get connection_id failed,error_code={},message={}",
+                    server_check_response.get_error_code(),
+                    server_check_response
+                        .get_message()
+                        .or(Some(&"".to_string()))
+                        .unwrap(),
+                )))?
+                .to_string()
+        };
+
+        let bi_client = BiRequestStreamClient::new(channel.clone());
+        let (mut client_sender, client_receiver) = bi_client.request_bi_stream()?;
+        // send a ConnectionSetupClientRequest
+        client_sender
+            .send((
+                payload_helper::build_req_grpc_payload(ConnectionSetupClientRequest::new(
+                    tenant, labels,
+                )),
+                grpcio::WriteFlags::default(),
+            ))
+            .await?;
+
+        Ok(State::Connected {
+            target,
+            conn_id,
+            channel,
+            client,
+            bi_client,
+            bi_sender: Arc::new(tokio::sync::Mutex::new(client_sender)),
+            bi_receiver: Arc::new(tokio::sync::Mutex::new(client_receiver)),
+        })
+    }
+
+    /// Build the channel, secured by TLS if configured.
+    fn build_channel(&self, target: &str) -> grpcio::Channel {
+        let env = Arc::new(grpcio::Environment::new(2));
+        let builder = grpcio::ChannelBuilder::new(env);
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &self.client_config.tls {
+            let builder = match &tls.server_name {
+                Some(server_name) => builder.override_ssl_target(server_name.clone()),
+                None => builder,
+            };
+            return builder.secure_connect(target, tls.channel_credentials());
+        }
+        builder.connect(target)
+    }
+
+    /// Reset the connection as ConnectResetRequest asks, connect to the given server,
+    /// or the next one in server list, then the bi-stream is migrated to the new connection.
+    /// Keep the current connection if the new one failed.
+    pub(crate) async fn reset(&mut self, server: Option<String>) {
+        let _connecting = self.connecting.lock().await;
+        if let Some(server) = server {
+            if self.try_reset(server).await {
+                return;
+            }
+        }
+        // rotate only if the given server failed or absent.
+        let next_server = self.server_list.next_server();
+        self.try_reset(next_server).await;
+    }
+
+    /// Connect to the target within CONNECT_TIMEOUT, so that a blackholed one does not block
+    /// the remote client loop, then replace the current connection. Return true if succeeded.
+    async fn try_reset(&self, target: String) -> bool {
+        match tokio::time::timeout(Self::CONNECT_TIMEOUT, self.try_connect(target.clone())).await {
+            Ok(Ok(connected)) => {
+                tracing::info!(to = %target, "reset connection successfully!");
+                self.set_state(connected);
+                true
+            }
+            Ok(Err(error)) => {
+                tracing::warn!(%error, to = %target, "error resetting connection");
+                false
+            }
+            Err(_) => {
+                tracing::warn!(to = %target, "resetting connection timeout");
+                false
+            }
+        }
+    }
+
+    /// Received something from server, the connection is alive.
+    fn mark_active(&self) {
+        loop {
+            let active_lock = self.last_active.try_lock();
+            if let Ok(mut mutex) = active_lock {
+                *mutex = Instant::now();
+                break;
+            }
+        }
+    }
+
+    fn idle_time(&self) -> Duration {
+        loop {
+            let active_lock = self.last_active.try_lock();
+            if let Ok(mutex) = active_lock {
+                return mutex.elapsed();
+            }
+        }
+    }
+
+    /// Send HealthCheckRequest if the connection is idle for the keep alive time, mark it
+    /// disconnected and reconnect once failed in a row for MAX_HEALTH_CHECK_FAILURES times,
+    /// so that the half-open connection is detected instead of hanging until the OS times out.
+    pub(crate) async fn health_check(&mut self) {
+        let conn_id = match self.connection_id() {
+            None => return,
+            Some(conn_id) => conn_id,
+        };
+        if self.idle_time() < Self::KEEP_ALIVE_TIME {
+            return;
+        }
+        let healthy = match self
+            .send_client_req_timeout(HealthCheckClientRequest::new(), Self::HEALTH_CHECK_TIMEOUT)
+            .await
+        {
+            Ok(payload_inner) => TYPE_HEALTH_CHECK_SERVER_RESPONSE.eq(&payload_inner.type_url),
+            Err(error) => {
+                tracing::warn!(%error, "health check failed");
+                false
+            }
+        };
+        if self.on_health_check(healthy) {
+            tracing::warn!(
+                conn_id = %conn_id,
+                failures = Self::MAX_HEALTH_CHECK_FAILURES,
+                "connection unhealthy, reconnecting"
+            );
+            self.set_disconnected(&conn_id);
+            self.connect().await;
+        }
+    }
+
+    /// Count the health check failed in a row, return true if the connection is unhealthy.
+    fn on_health_check(&self, healthy: bool) -> bool {
+        if healthy {
+            self.health_check_failures.store(0, Ordering::Relaxed);
+            return false;
+        }
+        let failures = self.health_check_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= Self::MAX_HEALTH_CHECK_FAILURES {
+            self.health_check_failures.store(0, Ordering::Relaxed);
+            return true;
+        }
+        false
+    }
+
+    /// The connection id given by server, None if disconnected.
+    /// A different one means reconnected.
+    pub(crate) fn connection_id(&self) -> Option<String> {
+        match self.state() {
+            State::Connected { conn_id, .. } => Some(conn_id),
+            State::Disconnected(_) => None,
         }
     }
 
     /// Listen a server_request from server by bi_receiver
     pub(crate) async fn next_server_req_payload(&mut self) -> Payload {
         loop {
-            match self.state {
+            match self.state() {
                 State::Connected {
-                    ref mut bi_receiver,
+                    conn_id,
+                    bi_receiver,
                     ..
-                } => match bi_receiver.to_owned().lock().unwrap().next().await {
-                    Some(Ok(payload)) => return payload,
+                } => match bi_receiver.lock().await.next().await {
+                    Some(Ok(payload)) => {
+                        self.mark_active();
+                        return payload;
+                    }
                     Some(Err(status)) => {
                         tracing::warn!(%status, "error from stream");
-                        self.state = State::Disconnected(Self::BACKOFF);
+                        self.set_disconnected(&conn_id);
                     }
                     None => {
                         tracing::error!("stream closed by server");
-                        self.state = State::Disconnected(Self::BACKOFF);
+                        self.set_disconnected(&conn_id);
                     }
                 },
                 State::Disconnected(_) => self.connect().await,
@@ -149,21 +401,26 @@ impl Connection {
         }
     }
 
-    /// Reply a client_resp to server by bi_sender
+    /// Reply a client_resp to server by bi_sender,
+    /// the connection is marked disconnected if failed, and reconnected by the next listening.
     pub(crate) async fn reply_client_resp(&mut self, resp: impl Response + serde::Serialize) {
-        match self.state {
+        match self.state() {
             State::Connected {
-                ref mut bi_sender, ..
-            } => bi_sender
-                .to_owned()
-                .lock()
-                .unwrap()
-                .send((
-                    payload_helper::build_resp_grpc_payload(resp),
-                    grpcio::WriteFlags::default(),
-                ))
-                .await
-                .unwrap(),
+                conn_id, bi_sender, ..
+            } => {
+                let sent = bi_sender
+                    .lock()
+                    .await
+                    .send((
+                        payload_helper::build_resp_grpc_payload(resp),
+                        grpcio::WriteFlags::default(),
+                    ))
+                    .await;
+                if let Err(e) = sent {
+                    tracing::warn!("reply to server failed, {:?}", e);
+                    self.set_disconnected(&conn_id);
+                }
+            }
             State::Disconnected(_) => self.connect().await,
         }
     }
@@ -173,11 +430,52 @@ impl Connection {
         &mut self,
         req: impl Request + serde::Serialize,
     ) -> crate::api::error::Result<Box<PayloadInner>> {
-        match self.state {
-            State::Connected { ref mut client, .. } => {
+        self.do_send_client_req(req, grpcio::CallOption::default())
+            .await
+    }
+
+    /// Send a client_req with timeout as the deadline, with get a server_resp,
+    /// return Error::Timeout if the deadline exceeded.
+    pub(crate) async fn send_client_req_timeout(
+        &mut self,
+        req: impl Request + serde::Serialize,
+        timeout: Duration,
+    ) -> crate::api::error::Result<Box<PayloadInner>> {
+        let req_type_url = req.get_type_url().clone();
+        let call_opt = grpcio::CallOption::default().timeout(timeout);
+        // the deadline also covers the reconnecting when disconnected.
+        match tokio::time::timeout(timeout, self.do_send_client_req(req, call_opt)).await {
+            Ok(Err(crate::api::error::Error::GrpcioJoin(grpcio::Error::RpcFailure(status))))
+                if status.code() == grpcio::RpcStatusCode::DEADLINE_EXCEEDED =>
+            {
+                Err(crate::api::error::Error::Timeout(format!(
+                    "{} exceeded {:?}",
+                    req_type_url, timeout
+                )))
+            }
+            Ok(result) => result,
+            Err(_) => Err(crate::api::error::Error::Timeout(format!(
+                "{} exceeded {:?}",
+                req_type_url, timeout
+            ))),
+        }
+    }
+
+    async fn do_send_client_req(
+        &mut self,
+        mut req: impl Request + serde::Serialize,
+        call_opt: grpcio::CallOption,
+    ) -> crate::api::error::Result<Box<PayloadInner>> {
+        match self.state() {
+            State::Connected { client, .. } => {
+                // consult the auth plugins, e.g. accessToken and signature.
+                req.add_headers(self.security_proxy.headers_for(&req.get_resource()));
                 let req_payload = payload_helper::build_req_grpc_payload(req);
-                let resp_payload = client.request(&req_payload)?;
-                Ok(Box::new(payload_helper::covert_payload(resp_payload)))
+                let resp_payload = client.request_async_opt(&req_payload, call_opt)?.await?;
+                self.mark_active();
+                let payload_inner = payload_helper::covert_payload(resp_payload);
+                check_no_right(&payload_inner)?;
+                Ok(Box::new(payload_inner))
             }
             State::Disconnected(_) => {
                 self.connect().await;
@@ -187,25 +485,35 @@ impl Connection {
             }
         }
     }
+}
 
-    /// Get a RequestClient, which use the core channel of connection.
-    pub(crate) fn get_client(&mut self) -> crate::api::error::Result<RequestClient> {
-        match self.state {
-            State::Connected { ref mut client, .. } => Ok(client.clone()),
-            State::Disconnected(_) => Err(crate::api::error::Error::ClientShutdown(String::from(
-                "Disconnected, please try later.",
-            ))),
-        }
+/// Nacos server responds ErrorResponse with 403, if not logged in or no permission.
+const NO_RIGHT_ERROR_CODE: u32 = 403;
+
+/// Return Error::AuthFailed if the server responds no right.
+fn check_no_right(payload_inner: &PayloadInner) -> crate::api::error::Result<()> {
+    if !TYPE_ERROR_SERVER_RESPONSE.eq(&payload_inner.type_url) {
+        return Ok(());
+    }
+    let error_resp: ErrorResponse = serde_json::from_str(payload_inner.body_str.as_str())?;
+    if error_resp.get_error_code() == NO_RIGHT_ERROR_CODE {
+        return Err(crate::api::error::Error::AuthFailed(format!(
+            "no right, message={}",
+            error_resp.get_message().unwrap_or(&"".to_string())
+        )));
     }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use crate::api::client_config::ClientConfig;
     use crate::common::remote::conn::Connection;
+    use crate::common::remote::request::client_request::ServerCheckClientRequest;
     use crate::common::remote::request::server_request::ClientDetectionServerRequest;
     use crate::common::remote::request::{Request, TYPE_CLIENT_DETECTION_SERVER_REQUEST};
     use crate::common::remote::response::client_response::ClientDetectionClientResponse;
+    use crate::common::remote::test_server::{start_test_server, TEST_CONNECTION_ID};
     use crate::common::util::payload_helper;
 
     // #[tokio::test]
@@ -218,6 +526,148 @@ mod tests {
         remote_connect.connect().await;
     }
 
+    #[tokio::test]
+    async fn test_send_client_req_timeout() {
+        let mut remote_connect =
+            Connection::new(ClientConfig::new().server_addr("127.0.0.1:1".to_string()));
+        let resp = remote_connect
+            .send_client_req_timeout(
+                ServerCheckClientRequest::new(),
+                std::time::Duration::from_millis(100),
+            )
+            .await;
+        assert!(matches!(resp, Err(crate::api::error::Error::Timeout(_))));
+    }
+
+    #[tokio::test]
+    async fn test_connect_once_all_servers_down() {
+        let mut remote_connect =
+            Connection::new(ClientConfig::new().server_addr("127.0.0.1:1,127.0.0.1:2"));
+        let connected = tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            remote_connect.connect_once(),
+        )
+        .await
+        .expect("connect_once should not block when all servers are down");
+        assert!(!connected);
+        assert_eq!(None, remote_connect.connection_id());
+    }
+
+    #[test]
+    fn test_check_no_right() {
+        use crate::common::remote::conn::check_no_right;
+        use crate::common::util::payload_helper::PayloadInner;
+        let payload_inner = |type_url: &str, body_str: &str| PayloadInner {
+            type_url: type_url.to_string(),
+            headers: Default::default(),
+            body_str: body_str.to_string(),
+        };
+        let no_right = payload_inner(
+            "ErrorResponse",
+            r#"{"resultCode":500,"errorCode":403,"message":"unknown user!"}"#,
+        );
+        assert!(matches!(
+            check_no_right(&no_right),
+            Err(crate::api::error::Error::AuthFailed(_))
+        ));
+        let other_error = payload_inner(
+            "ErrorResponse",
+            r#"{"resultCode":500,"errorCode":500,"message":"error"}"#,
+        );
+        assert!(check_no_right(&other_error).is_ok());
+        let ok = payload_inner("HealthCheckResponse", "{}");
+        assert!(check_no_right(&ok).is_ok());
+    }
+
+    #[test]
+    fn test_unhealthy_after_health_check_failures() {
+        let remote_connect = Connection::new(ClientConfig::new());
+        assert!(!remote_connect.on_health_check(false));
+        assert!(!remote_connect.on_health_check(false));
+        // succeeded, count again.
+        assert!(!remote_connect.on_health_check(true));
+        assert!(!remote_connect.on_health_check(false));
+        assert!(!remote_connect.on_health_check(false));
+        assert!(remote_connect.on_health_check(false));
+        assert!(!remote_connect.on_health_check(false));
+    }
+
+    #[tokio::test]
+    async fn test_reset() {
+        let (_server, port) =
+            start_test_server("127.0.0.1:0", grpcio::ServerCredentials::insecure());
+        let server_addr = format!("127.0.0.1:{}", port);
+        let mut remote_connect = Connection::new(
+            ClientConfig::new().server_addrs([server_addr.clone(), "127.0.0.1:1".to_string()]),
+        );
+        assert!(remote_connect.connect_once().await);
+
+        // connected to the given server, the rotation is kept.
+        remote_connect.reset(Some(server_addr.clone())).await;
+        assert_eq!(
+            Some(TEST_CONNECTION_ID.to_string()),
+            remote_connect.connection_id()
+        );
+        assert_eq!(server_addr, remote_connect.server_list.current_server());
+
+        // the given server is down, switch to the next one, also down, keep the current.
+        remote_connect.reset(Some("127.0.0.1:1".to_string())).await;
+        assert_eq!("127.0.0.1:1", remote_connect.server_list.current_server());
+        assert!(remote_connect.connection_id().is_some());
+    }
+
+    /// The self-signed CA and the server cert for `nacos.test`, by testdata/tls/gen.sh.
+    #[cfg(feature = "tls")]
+    fn read_tls_testdata(file: &str) -> Vec<u8> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata")
+            .join("tls")
+            .join(file);
+        std::fs::read(path).unwrap()
+    }
+
+    /// Start the test server secured by the cert for `nacos.test`, return it with the port.
+    #[cfg(feature = "tls")]
+    fn start_tls_test_server() -> (grpcio::Server, u16) {
+        let credentials = grpcio::ServerCredentialsBuilder::new()
+            .add_cert(
+                read_tls_testdata("server.pem"),
+                read_tls_testdata("server.key"),
+            )
+            .build();
+        start_test_server("127.0.0.1:0", credentials)
+    }
+
+    #[cfg(feature = "tls")]
+    #[tokio::test]
+    async fn test_tls_connect() {
+        use crate::api::client_config::TlsConfig;
+        let (_server, port) = start_tls_test_server();
+        let server_addr = format!("127.0.0.1:{}", port);
+
+        let mut remote_connect = Connection::new(
+            ClientConfig::new().server_addr(server_addr.clone()).tls(
+                TlsConfig::new()
+                    .ca_cert(read_tls_testdata("ca.pem"))
+                    .server_name("nacos.test"),
+            ),
+        );
+        assert!(remote_connect.connect_once().await);
+        assert_eq!(
+            Some(TEST_CONNECTION_ID.to_string()),
+            remote_connect.connection_id()
+        );
+
+        // the self-signed CA is not trusted by default.
+        let mut untrusted = Connection::new(
+            ClientConfig::new()
+                .server_addr(server_addr)
+                .tls(TlsConfig::new().server_name("nacos.test")),
+        );
+        assert!(!untrusted.connect_once().await);
+        assert!(untrusted.connection_id().is_none());
+    }
+
     // #[tokio::test]
     async fn test_next_server_request() {
         tracing_subscriber::fmt()
@@ -2,3 +2,7 @@ pub(crate) mod conn;
 pub(crate) mod remote_client;
 pub(crate) mod request;
 pub(crate) mod response;
+pub(crate) mod security;
+pub(crate) mod server_list;
+#[cfg(test)]
+pub(crate) mod test_server;
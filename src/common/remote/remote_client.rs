@@ -1,3 +1,4 @@
+use std::time::Duration;
 use tokio::sync::mpsc::{Receiver, Sender};
 
 use crate::api::client_config::ClientConfig;
@@ -63,6 +64,118 @@ impl GrpcRemoteClient {
     }
 }
 
+/// The interval of checking reconnect and redo.
+const REDO_INTERVAL: Duration = Duration::from_secs(3);
+
+/// The service specific part of the remote client loop, e.g. config and naming.
+/// Runs on the current thread runtime of the loop, so the futures need not be Send.
+#[async_trait::async_trait(?Send)]
+pub(crate) trait ServerRequestHandler: Send + 'static {
+    /// Deal with the server request, except ClientDetection and ConnectReset which are
+    /// dealt with by the loop.
+    async fn handle_server_request(&mut self, conn: &mut Connection, payload_inner: PayloadInner);
+
+    /// Redo on the current connection, called every REDO_INTERVAL when connected,
+    /// reconnected is true on the first call after the connection changed.
+    async fn redo(&mut self, conn: &mut Connection, reconnected: bool);
+
+    /// The interval of `on_interval`, None if nothing to do periodically.
+    fn interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Called every `interval`.
+    async fn on_interval(&mut self, _conn: &mut Connection) {}
+}
+
+/// Start the remote client loop in a thread named as given: health check when idle,
+/// reply ClientDetection, switch server on ConnectReset, deal with the other server requests
+/// and redo after reconnect by the handler.
+pub(crate) fn start_remote_client(
+    name: &str,
+    mut conn: Connection,
+    mut handler: impl ServerRequestHandler,
+) {
+    let thread_name = name.to_string();
+    let _conn_thread = std::thread::Builder::new()
+        .name(thread_name.clone())
+        .spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_io()
+                .enable_time()
+                .build()
+                .unwrap_or_else(|err| panic!("{} runtime initialization failed, {}", thread_name, err));
+
+            runtime.block_on(async move {
+                let mut health_check_interval = tokio::time::interval_at(
+                    tokio::time::Instant::now() + Connection::HEALTH_CHECK_INTERVAL,
+                    Connection::HEALTH_CHECK_INTERVAL,
+                );
+                let mut handler_interval = handler.interval().map(|interval| {
+                    tokio::time::interval_at(tokio::time::Instant::now() + interval, interval)
+                });
+                let mut redo_interval = tokio::time::interval_at(
+                    tokio::time::Instant::now() + REDO_INTERVAL,
+                    REDO_INTERVAL,
+                );
+                let mut conn_id = conn.connection_id();
+                loop {
+                    // reconnect outside the select, otherwise the backoff sleep is cancelled
+                    // and started over by the intervals once it is longer than them.
+                    if conn.connection_id().is_none() {
+                        conn.connect().await;
+                        continue;
+                    }
+                    tokio::select! { biased;
+                        // health check when idle, reconnect if the connection is half-open.
+                        _ = health_check_interval.tick() => {
+                            conn.health_check().await;
+                        },
+                        // deal with next_server_req_payload, basic conn interaction logic.
+                        server_req_payload = conn.next_server_req_payload() => {
+                            let payload_inner = payload_helper::covert_payload(server_req_payload);
+                            if TYPE_CLIENT_DETECTION_SERVER_REQUEST.eq(&payload_inner.type_url) {
+                                let de = ClientDetectionServerRequest::from(payload_inner.body_str.as_str()).headers(payload_inner.headers);
+                                conn.reply_client_resp(ClientDetectionClientResponse::new(de.get_request_id().clone())).await;
+                            } else if TYPE_CONNECT_RESET_SERVER_REQUEST.eq(&payload_inner.type_url) {
+                                let de = ConnectResetServerRequest::from(payload_inner.body_str.as_str()).headers(payload_inner.headers);
+                                conn.reply_client_resp(ConnectResetClientResponse::new(de.get_request_id().clone())).await;
+                                // switch to the server given, migrate the bi-stream, then redo on the new connection.
+                                conn.reset(de.server_addr()).await;
+                            } else {
+                                handler.handle_server_request(&mut conn, payload_inner).await;
+                            }
+                        },
+                        _ = tick(&mut handler_interval) => {
+                            handler.on_interval(&mut conn).await;
+                        },
+                        // redo once reconnected, the handler retries the failed ones.
+                        _ = redo_interval.tick() => {
+                            let current_conn_id = conn.connection_id();
+                            if current_conn_id.is_none() {
+                                continue;
+                            }
+                            let reconnected = current_conn_id != conn_id;
+                            conn_id = current_conn_id;
+                            handler.redo(&mut conn, reconnected).await;
+                        },
+                    }
+                }
+            });
+        })
+        .unwrap_or_else(|err| panic!("{} could not spawn thread, {}", name, err));
+}
+
+/// Tick the interval if any, or pending forever.
+async fn tick(interval: &mut Option<tokio::time::Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
@@ -70,7 +183,49 @@ mod tests {
     use tokio::time::sleep;
 
     use crate::api::client_config::ClientConfig;
-    use crate::common::remote::remote_client::GrpcRemoteClient;
+    use crate::common::remote::conn::Connection;
+    use crate::common::remote::remote_client::{
+        start_remote_client, GrpcRemoteClient, ServerRequestHandler,
+    };
+    use crate::common::remote::test_server::{start_test_server, TEST_CONNECTION_ID};
+    use crate::common::util::payload_helper::PayloadInner;
+
+    struct NoopHandler;
+
+    #[async_trait::async_trait(?Send)]
+    impl ServerRequestHandler for NoopHandler {
+        async fn handle_server_request(&mut self, _conn: &mut Connection, _payload: PayloadInner) {}
+
+        async fn redo(&mut self, _conn: &mut Connection, _reconnected: bool) {}
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_after_long_outage() {
+        // the port is free, the server is down until started below.
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server_addr = format!("127.0.0.1:{}", port);
+        let conn = Connection::new(ClientConfig::new().server_addr(server_addr.clone()));
+        start_remote_client("test-reconnect-remote-client", conn.clone(), NoopHandler);
+
+        // long enough for the backoff to exceed the redo and health check intervals.
+        sleep(Duration::from_secs(11)).await;
+        assert!(conn.connection_id().is_none());
+
+        let (_server, _) = start_test_server(&server_addr, grpcio::ServerCredentials::insecure());
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(15);
+        while conn.connection_id().is_none() {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "not reconnected after the server is up"
+            );
+            sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(Some(TEST_CONNECTION_ID.to_string()), conn.connection_id());
+    }
 
     // #[tokio::test]
     async fn test_grpc_remote_client() {
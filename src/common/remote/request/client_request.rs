@@ -17,6 +17,9 @@ impl Request for ServerCheckClientRequest {
     fn get_headers(&self) -> &HashMap<String, String> {
         &self.headers
     }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
     fn get_type_url(&self) -> &String {
         &TYPE_SERVER_CHECK_CLIENT_REQUEST
     }
@@ -49,6 +52,9 @@ impl Request for ConnectionSetupClientRequest {
     fn get_headers(&self) -> &HashMap<String, String> {
         &self.headers
     }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
     fn get_type_url(&self) -> &String {
         &TYPE_CONNECT_SETUP_CLIENT_REQUEST
     }
@@ -86,6 +92,9 @@ impl Request for HealthCheckClientRequest {
     fn get_headers(&self) -> &HashMap<String, String> {
         &self.headers
     }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
     fn get_type_url(&self) -> &String {
         &TYPE_HEALTH_CHECK_CLIENT_REQUEST
     }
@@ -2,13 +2,24 @@ use lazy_static::lazy_static;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicI64, Ordering};
 
+pub(crate) use crate::api::auth::RequestResource;
+
 pub(crate) mod client_request;
 pub(crate) mod server_request;
 
 pub(crate) trait Request {
     fn get_request_id(&self) -> &String;
     fn get_headers(&self) -> &HashMap<String, String>;
+    fn headers_mut(&mut self) -> &mut HashMap<String, String>;
+    /// Add headers, e.g. the signature of request.
+    fn add_headers(&mut self, headers: HashMap<String, String>) {
+        self.headers_mut().extend(headers);
+    }
     fn get_type_url(&self) -> &String;
+    /// The resource accessed by request, for signing it.
+    fn get_resource(&self) -> RequestResource {
+        RequestResource::None
+    }
 }
 
 lazy_static! {
@@ -33,6 +44,10 @@ lazy_static! {
     /// com.alibaba.nacos.api.config.remote.request.ConfigChangeNotifyRequest
     pub static ref TYPE_CONFIG_CHANGE_NOTIFY_SERVER_REQUEST: String = String::from("ConfigChangeNotifyRequest");
 
+    // --- naming server req ---
+    /// com.alibaba.nacos.api.naming.remote.request.NotifySubscriberRequest
+    pub static ref TYPE_NOTIFY_SUBSCRIBER_SERVER_REQUEST: String = String::from("NotifySubscriberRequest");
+
     // --- config client req ---
     /// com.alibaba.nacos.api.config.remote.request.ConfigBatchListenRequest
     pub static ref TYPE_CONFIG_BATCH_LISTEN_CLIENT_REQUEST: String = String::from("ConfigBatchListenRequest");
@@ -40,6 +55,22 @@ lazy_static! {
     /// com.alibaba.nacos.api.config.remote.request.ConfigQueryRequest
     pub static ref TYPE_CONFIG_QUERY_CLIENT_REQUEST: String = String::from("ConfigQueryRequest");
 
+    /// com.alibaba.nacos.api.config.remote.request.ConfigPublishRequest
+    pub static ref TYPE_CONFIG_PUBLISH_CLIENT_REQUEST: String = String::from("ConfigPublishRequest");
+
+    /// com.alibaba.nacos.api.config.remote.request.ConfigRemoveRequest
+    pub static ref TYPE_CONFIG_REMOVE_CLIENT_REQUEST: String = String::from("ConfigRemoveRequest");
+
+    // --- naming client req ---
+    /// com.alibaba.nacos.api.naming.remote.request.InstanceRequest
+    pub static ref TYPE_INSTANCE_CLIENT_REQUEST: String = String::from("InstanceRequest");
+
+    /// com.alibaba.nacos.api.naming.remote.request.ServiceQueryRequest
+    pub static ref TYPE_SERVICE_QUERY_CLIENT_REQUEST: String = String::from("ServiceQueryRequest");
+
+    /// com.alibaba.nacos.api.naming.remote.request.SubscribeServiceRequest
+    pub static ref TYPE_SUBSCRIBE_SERVICE_CLIENT_REQUEST: String = String::from("SubscribeServiceRequest");
+
 }
 
 // odd by client request id.
@@ -19,6 +19,9 @@ impl Request for ConnectResetServerRequest {
     fn get_headers(&self) -> &HashMap<String, String> {
         &self.headers
     }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
     fn get_type_url(&self) -> &String {
         &TYPE_CONNECT_RESET_SERVER_REQUEST
     }
@@ -38,6 +41,15 @@ impl ConnectResetServerRequest {
     pub fn headers(self, headers: HashMap<String, String>) -> Self {
         ConnectResetServerRequest { headers, ..self }
     }
+
+    /// The grpc address of the server to reset to, None if not given.
+    pub fn server_addr(&self) -> Option<String> {
+        let ip = self.serverIp.as_ref().filter(|ip| !ip.is_empty())?;
+        let http_port = self.serverPort.as_ref().and_then(|port| port.parse().ok());
+        Some(crate::common::remote::server_list::grpc_server_addr(
+            ip, http_port,
+        ))
+    }
 }
 
 impl From<&str> for ConnectResetServerRequest {
@@ -61,6 +73,9 @@ impl Request for ClientDetectionServerRequest {
     fn get_headers(&self) -> &HashMap<String, String> {
         &self.headers
     }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
     fn get_type_url(&self) -> &String {
         &TYPE_CLIENT_DETECTION_SERVER_REQUEST
     }
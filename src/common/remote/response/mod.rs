@@ -51,4 +51,27 @@ lazy_static! {
     /// com.alibaba.nacos.api.config.remote.response.ConfigChangeBatchListenResponse
     pub static ref TYPE_CONFIG_CHANGE_BATCH_LISTEN_RESPONSE: String = String::from("ConfigChangeBatchListenResponse");
 
+    /// com.alibaba.nacos.api.config.remote.response.ConfigQueryResponse
+    pub static ref TYPE_CONFIG_QUERY_SERVER_RESPONSE: String = String::from("ConfigQueryResponse");
+
+    /// com.alibaba.nacos.api.config.remote.response.ConfigPublishResponse
+    pub static ref TYPE_CONFIG_PUBLISH_SERVER_RESPONSE: String = String::from("ConfigPublishResponse");
+
+    /// com.alibaba.nacos.api.config.remote.response.ConfigRemoveResponse
+    pub static ref TYPE_CONFIG_REMOVE_SERVER_RESPONSE: String = String::from("ConfigRemoveResponse");
+
+    // --- naming client resp ---
+    /// com.alibaba.nacos.api.naming.remote.response.NotifySubscriberResponse
+    pub static ref TYPE_NOTIFY_SUBSCRIBER_CLIENT_RESPONSE: String = String::from("NotifySubscriberResponse");
+
+    // --- naming server resp ---
+    /// com.alibaba.nacos.api.naming.remote.response.InstanceResponse
+    pub static ref TYPE_INSTANCE_SERVER_RESPONSE: String = String::from("InstanceResponse");
+
+    /// com.alibaba.nacos.api.naming.remote.response.QueryServiceResponse
+    pub static ref TYPE_SERVICE_QUERY_SERVER_RESPONSE: String = String::from("QueryServiceResponse");
+
+    /// com.alibaba.nacos.api.naming.remote.response.SubscribeServiceResponse
+    pub static ref TYPE_SUBSCRIBE_SERVICE_SERVER_RESPONSE: String = String::from("SubscribeServiceResponse");
+
 }
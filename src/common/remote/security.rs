@@ -0,0 +1,590 @@
+use crate::api::auth::{AuthPlugin, RequestResource};
+use crate::api::client_config::ClientConfig;
+use crate::api::constants::SERVICE_INFO_SPLITER;
+use crate::common::remote::server_list::{http_server_addr, ServerListManager};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The interval of checking whether the access token needs refreshing.
+const REFRESH_TOKEN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The timeout of login.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The header key of access token in request headers.
+const ACCESS_TOKEN_KEY: &str = "accessToken";
+
+/// The header keys of config request signature.
+const SPAS_ACCESS_KEY: &str = "Spas-AccessKey";
+const SPAS_TIMESTAMP: &str = "Timestamp";
+const SPAS_SIGNATURE: &str = "Spas-Signature";
+
+/// The header keys of naming request signature, as the naming server checks.
+const NAMING_ACCESS_KEY: &str = "ak";
+const NAMING_SIGN_DATA: &str = "data";
+const NAMING_SIGNATURE: &str = "signature";
+
+/// The chain of auth plugins, the built-in username/password and access_key/secret_key
+/// ones first if configured, then the custom ones from ClientConfig.
+#[derive(Clone)]
+pub(crate) struct SecurityProxy {
+    auth_plugins: Vec<Arc<dyn AuthPlugin>>,
+    started: Arc<AtomicBool>,
+}
+
+impl SecurityProxy {
+    pub(crate) fn new(client_config: &ClientConfig) -> Self {
+        let mut auth_plugins: Vec<Arc<dyn AuthPlugin>> = Vec::new();
+        if let Some(username) = &client_config.username {
+            let auth_plugin = UsernamePasswordAuthPlugin::new(
+                username.clone(),
+                client_config.password.clone().unwrap_or_default(),
+            );
+            #[cfg(feature = "tls")]
+            let auth_plugin = match &client_config.tls {
+                Some(tls) => auth_plugin.with_tls(tls),
+                None => auth_plugin,
+            };
+            auth_plugins.push(Arc::new(auth_plugin));
+        }
+        if let (Some(access_key), Some(secret_key)) =
+            (&client_config.access_key, &client_config.secret_key)
+        {
+            auth_plugins.push(Arc::new(AccessKeyAuthPlugin {
+                access_key: access_key.clone(),
+                secret_key: secret_key.clone(),
+            }));
+        }
+        auth_plugins.extend(client_config.auth_plugins.iter().cloned());
+        Self {
+            auth_plugins,
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Login by all plugins, then refresh them periodically, only once.
+    pub(crate) async fn start(&self, server_list: ServerListManager) {
+        if self.auth_plugins.is_empty() {
+            return;
+        }
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let server_addrs = http_server_addrs(&server_list);
+        for auth_plugin in self.auth_plugins.iter() {
+            auth_plugin.login(&server_addrs).await;
+        }
+
+        let auth_plugins = self.auth_plugins.clone();
+        let _refresh_thread = std::thread::Builder::new()
+            .name("security-token-refresher".into())
+            .spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_io()
+                    .enable_time()
+                    .build()
+                    .expect("security-token-refresher runtime initialization failed");
+
+                runtime.block_on(async move {
+                    let mut refresh_interval = tokio::time::interval_at(
+                        tokio::time::Instant::now() + REFRESH_TOKEN_INTERVAL,
+                        REFRESH_TOKEN_INTERVAL,
+                    );
+                    loop {
+                        refresh_interval.tick().await;
+                        let server_addrs = http_server_addrs(&server_list);
+                        for auth_plugin in auth_plugins.iter() {
+                            auth_plugin.refresh(&server_addrs).await;
+                        }
+                    }
+                });
+            })
+            .expect("security-token-refresher could not spawn thread");
+    }
+
+    /// The headers of all plugins for the resource requested, the latter wins if conflicts.
+    pub(crate) fn headers_for(&self, resource: &RequestResource) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        for auth_plugin in self.auth_plugins.iter() {
+            headers.extend(auth_plugin.headers_for(resource));
+        }
+        headers
+    }
+}
+
+fn http_server_addrs(server_list: &ServerListManager) -> Vec<String> {
+    server_list
+        .servers()
+        .iter()
+        .map(|server| http_server_addr(server))
+        .collect()
+}
+
+/// Login the auth-enabled server with username and password, keep the access token
+/// fresh before its ttl expires, then it is attached to the request headers.
+struct UsernamePasswordAuthPlugin {
+    username: String,
+    password: String,
+    /// http, or https if TLS configured.
+    scheme: &'static str,
+    http_client: reqwest::Client,
+    access_token: Mutex<Option<AccessToken>>,
+}
+
+struct AccessToken {
+    token: String,
+    ttl: Duration,
+    refreshed_at: Instant,
+}
+
+impl AccessToken {
+    /// Refresh it in the last tenth of ttl.
+    fn need_refresh(&self) -> bool {
+        self.refreshed_at.elapsed() >= self.ttl - self.ttl / 10
+    }
+}
+
+/// com.alibaba.nacos.plugin.auth.impl.JwtTokenManager login result
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LoginResponse {
+    access_token: String,
+    /// seconds
+    token_ttl: u64,
+}
+
+impl UsernamePasswordAuthPlugin {
+    fn new(username: String, password: String) -> Self {
+        Self {
+            username,
+            password,
+            scheme: "http",
+            http_client: reqwest::Client::new(),
+            access_token: Mutex::new(None),
+        }
+    }
+
+    /// Login over https, the server is verified with the CA cert of TLS settings if given.
+    #[cfg(feature = "tls")]
+    fn with_tls(mut self, tls: &crate::api::client_config::TlsConfig) -> Self {
+        let mut builder = reqwest::Client::builder();
+        if let Some(ca_cert) = &tls.ca_cert {
+            match reqwest::Certificate::from_pem(ca_cert) {
+                Ok(ca_cert) => builder = builder.add_root_certificate(ca_cert),
+                Err(err) => tracing::warn!("invalid ca_cert of TLS settings, {:?}", err),
+            }
+        }
+        self.scheme = "https";
+        self.http_client = builder.build().unwrap_or_default();
+        self
+    }
+
+    fn need_refresh(&self) -> bool {
+        loop {
+            let token_lock = self.access_token.try_lock();
+            if let Ok(mutex) = token_lock {
+                return mutex.as_ref().map_or(true, AccessToken::need_refresh);
+            }
+        }
+    }
+
+    fn set_access_token(&self, access_token: AccessToken) {
+        loop {
+            let token_lock = self.access_token.try_lock();
+            if let Ok(mut mutex) = token_lock {
+                *mutex = Some(access_token);
+                break;
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuthPlugin for UsernamePasswordAuthPlugin {
+    /// Login to the servers one by one until it succeeds.
+    async fn login(&self, server_addrs: &[String]) -> bool {
+        for server_addr in server_addrs {
+            let base_url = format!("{}://{}", self.scheme, server_addr);
+            let login_result =
+                login(&self.http_client, &base_url, &self.username, &self.password).await;
+            match login_result {
+                Ok(login_resp) => {
+                    tracing::debug!("login {} successfully!", server_addr);
+                    self.set_access_token(AccessToken {
+                        token: login_resp.access_token,
+                        ttl: Duration::from_secs(login_resp.token_ttl),
+                        refreshed_at: Instant::now(),
+                    });
+                    return true;
+                }
+                Err(err) => tracing::warn!("login {} failed, {:?}", server_addr, err),
+            }
+        }
+        false
+    }
+
+    async fn refresh(&self, server_addrs: &[String]) {
+        if self.need_refresh() {
+            self.login(server_addrs).await;
+        }
+    }
+
+    /// The accessToken if logged in.
+    fn headers_for(&self, _resource: &RequestResource) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        loop {
+            let token_lock = self.access_token.try_lock();
+            if let Ok(mutex) = token_lock {
+                if let Some(access_token) = mutex.as_ref() {
+                    headers.insert(ACCESS_TOKEN_KEY.to_string(), access_token.token.clone());
+                }
+                break;
+            }
+        }
+        headers
+    }
+}
+
+/// Sign the requests with access_key and secret_key, for the cloud deployments.
+struct AccessKeyAuthPlugin {
+    access_key: String,
+    secret_key: String,
+}
+
+#[async_trait::async_trait]
+impl AuthPlugin for AccessKeyAuthPlugin {
+    async fn login(&self, _server_addrs: &[String]) -> bool {
+        true
+    }
+
+    fn headers_for(&self, resource: &RequestResource) -> HashMap<String, String> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+        sign_headers(&self.access_key, &self.secret_key, resource, timestamp)
+    }
+}
+
+/// Config: Spas-Signature over tenant+group+timestamp,
+/// naming: signature over timestamp@@group@@serviceName.
+fn sign_headers(
+    access_key: &str,
+    secret_key: &str,
+    resource: &RequestResource,
+    timestamp: u128,
+) -> HashMap<String, String> {
+    let mut headers = HashMap::new();
+    match resource {
+        RequestResource::None => {}
+        RequestResource::Config { tenant, group } => {
+            let sign_resource = [tenant.as_str(), group.as_str()]
+                .into_iter()
+                .filter(|s| !s.trim().is_empty())
+                .collect::<Vec<&str>>()
+                .join("+");
+            let sign_data = if sign_resource.is_empty() {
+                timestamp.to_string()
+            } else {
+                format!("{}+{}", sign_resource, timestamp)
+            };
+            headers.insert(SPAS_ACCESS_KEY.to_string(), access_key.to_string());
+            headers.insert(SPAS_TIMESTAMP.to_string(), timestamp.to_string());
+            headers.insert(
+                SPAS_SIGNATURE.to_string(),
+                sign_with_hmac_sha1(&sign_data, secret_key),
+            );
+        }
+        RequestResource::Naming {
+            group_name,
+            service_name,
+            ..
+        } => {
+            let sign_resource = if group_name.trim().is_empty() {
+                service_name.clone()
+            } else {
+                format!("{}{}{}", group_name, SERVICE_INFO_SPLITER, service_name)
+            };
+            let sign_data = if service_name.is_empty() {
+                timestamp.to_string()
+            } else {
+                format!("{}{}{}", timestamp, SERVICE_INFO_SPLITER, sign_resource)
+            };
+            headers.insert(NAMING_ACCESS_KEY.to_string(), access_key.to_string());
+            headers.insert(
+                NAMING_SIGNATURE.to_string(),
+                sign_with_hmac_sha1(&sign_data, secret_key),
+            );
+            headers.insert(NAMING_SIGN_DATA.to_string(), sign_data);
+        }
+    }
+    headers
+}
+
+/// Base64 of HMAC-SHA1.
+fn sign_with_hmac_sha1(data: &str, key: &str) -> String {
+    let mut mac =
+        Hmac::<sha1::Sha1>::new_from_slice(key.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(data.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes())
+}
+
+/// POST {base_url}/nacos/v1/auth/users/login, base_url like http://127.0.0.1:8848
+async fn login(
+    http_client: &reqwest::Client,
+    base_url: &str,
+    username: &str,
+    password: &str,
+) -> crate::api::error::Result<LoginResponse> {
+    let url = format!("{}/nacos/v1/auth/users/login", base_url);
+    let resp = http_client
+        .post(url)
+        .form(&[("username", username), ("password", password)])
+        .timeout(LOGIN_TIMEOUT)
+        .send()
+        .await?;
+    if resp.status() == reqwest::StatusCode::FORBIDDEN {
+        return Err(crate::api::error::Error::AuthFailed(format!(
+            "login {} with username {} forbidden, {}",
+            base_url,
+            username,
+            resp.text().await?
+        )));
+    }
+    let login_resp: LoginResponse =
+        serde_json::from_str(resp.error_for_status()?.text().await?.as_str())?;
+    Ok(login_resp)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::auth::{AuthPlugin, RequestResource};
+    use crate::api::client_config::ClientConfig;
+    use crate::common::remote::security::{
+        login, sign_headers, sign_with_hmac_sha1, AccessToken, LoginResponse, SecurityProxy,
+        UsernamePasswordAuthPlugin,
+    };
+    use std::collections::HashMap;
+    use std::time::{Duration, Instant};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Serve one http request with the status and body, return the server addr.
+    async fn serve_once(status: &'static str, body: &'static str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server_addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            // read the whole request before responding, headers then the form body.
+            let mut req = Vec::new();
+            let mut buf = [0u8; 1024];
+            loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                req.extend_from_slice(&buf[..n]);
+                let req_str = String::from_utf8_lossy(&req).to_lowercase();
+                if let Some((headers, body)) = req_str.split_once("\r\n\r\n") {
+                    let content_length = headers
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map_or(0, |len| len.trim().parse::<usize>().unwrap());
+                    if body.len() >= content_length {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+            let resp = format!(
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            stream.write_all(resp.as_bytes()).await.unwrap();
+        });
+        server_addr
+    }
+
+    #[test]
+    fn test_access_token() {
+        let login_resp: LoginResponse = serde_json::from_str(
+            r#"{"accessToken":"token-1","tokenTtl":18000,"globalAdmin":true}"#,
+        )
+        .unwrap();
+        assert_eq!("token-1", login_resp.access_token);
+
+        let auth_plugin = UsernamePasswordAuthPlugin::new("nacos".to_string(), "".to_string());
+        assert!(auth_plugin.need_refresh());
+        assert!(auth_plugin.headers_for(&RequestResource::None).is_empty());
+
+        auth_plugin.set_access_token(AccessToken {
+            token: login_resp.access_token,
+            ttl: Duration::from_secs(login_resp.token_ttl),
+            refreshed_at: Instant::now(),
+        });
+        assert!(!auth_plugin.need_refresh());
+        assert_eq!(
+            Some(&"token-1".to_string()),
+            auth_plugin
+                .headers_for(&RequestResource::None)
+                .get("accessToken")
+        );
+
+        auth_plugin.set_access_token(AccessToken {
+            token: "token-2".to_string(),
+            ttl: Duration::from_millis(10),
+            refreshed_at: Instant::now() - Duration::from_millis(9),
+        });
+        assert!(auth_plugin.need_refresh());
+    }
+
+    #[test]
+    fn test_refresh_at_ninety_percent_of_ttl() {
+        let access_token = |elapsed: u64| AccessToken {
+            token: "token".to_string(),
+            ttl: Duration::from_secs(100),
+            refreshed_at: Instant::now() - Duration::from_secs(elapsed),
+        };
+        assert!(!access_token(0).need_refresh());
+        assert!(!access_token(89).need_refresh());
+        assert!(access_token(90).need_refresh());
+        assert!(access_token(100).need_refresh());
+    }
+
+    #[tokio::test]
+    async fn test_login() {
+        let server_addr = serve_once(
+            "200 OK",
+            r#"{"accessToken":"token-1","tokenTtl":18000,"globalAdmin":true}"#,
+        )
+        .await;
+        let auth_plugin = UsernamePasswordAuthPlugin::new("nacos".to_string(), "nacos".to_string());
+        assert!(auth_plugin.login(&[server_addr]).await);
+        assert!(!auth_plugin.need_refresh());
+        assert_eq!(
+            "token-1",
+            auth_plugin.headers_for(&RequestResource::None)["accessToken"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_login_forbidden() {
+        let server_addr = serve_once("403 Forbidden", "unknown user!").await;
+        let login_result = login(
+            &reqwest::Client::new(),
+            &format!("http://{}", server_addr),
+            "nacos",
+            "wrong",
+        )
+        .await;
+        match login_result {
+            Err(crate::api::error::Error::AuthFailed(msg)) => {
+                assert!(msg.contains("unknown user!"))
+            }
+            other => panic!("expect AuthFailed, but {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_login_scheme() {
+        let auth_plugin = UsernamePasswordAuthPlugin::new("nacos".to_string(), "".to_string());
+        assert_eq!("http", auth_plugin.scheme);
+        #[cfg(feature = "tls")]
+        assert_eq!(
+            "https",
+            auth_plugin
+                .with_tls(&crate::api::client_config::TlsConfig::new())
+                .scheme
+        );
+    }
+
+    #[test]
+    fn test_sign_headers() {
+        assert_eq!(
+            "3nybhbi3iqa8ino29wqQcBydtNk=",
+            sign_with_hmac_sha1("The quick brown fox jumps over the lazy dog", "key")
+        );
+
+        let config = RequestResource::Config {
+            tenant: "ns".to_string(),
+            group: "DEFAULT_GROUP".to_string(),
+        };
+        let headers = sign_headers("ak", "sk", &config, 1666000000000);
+        assert_eq!("ak", headers["Spas-AccessKey"]);
+        assert_eq!("1666000000000", headers["Timestamp"]);
+        assert_eq!("Q9EoBdZC5HQLiHXNQlDwWtj1VgY=", headers["Spas-Signature"]);
+
+        let batch_listen = RequestResource::Config {
+            tenant: "".to_string(),
+            group: "".to_string(),
+        };
+        let headers = sign_headers("ak", "sk", &batch_listen, 1666000000000);
+        assert_eq!("1C09O3RoVq09e/3SLL3tIEeQfQk=", headers["Spas-Signature"]);
+
+        let naming = RequestResource::Naming {
+            namespace: "ns".to_string(),
+            group_name: "DEFAULT_GROUP".to_string(),
+            service_name: "test-service".to_string(),
+        };
+        let headers = sign_headers("ak", "sk", &naming, 1666000000000);
+        assert_eq!("ak", headers["ak"]);
+        assert_eq!(
+            "1666000000000@@DEFAULT_GROUP@@test-service",
+            headers["data"]
+        );
+        assert_eq!("6TG/b6tF1/ZaxorOKak6dSW/jHY=", headers["signature"]);
+
+        assert!(sign_headers("ak", "sk", &RequestResource::None, 1666000000000).is_empty());
+        let not_signed = SecurityProxy::new(&ClientConfig::new().access_key("ak"));
+        assert!(not_signed.headers_for(&config).is_empty());
+    }
+
+    struct CustomAuthPlugin;
+
+    #[async_trait::async_trait]
+    impl AuthPlugin for CustomAuthPlugin {
+        async fn login(&self, _server_addrs: &[String]) -> bool {
+            true
+        }
+
+        fn headers_for(&self, resource: &RequestResource) -> HashMap<String, String> {
+            let mut headers = HashMap::new();
+            if let RequestResource::Config { group, .. } = resource {
+                headers.insert("custom-token".to_string(), group.clone());
+            }
+            headers
+        }
+    }
+
+    #[test]
+    fn test_chain_auth_plugins() {
+        let security_proxy = SecurityProxy::new(
+            &ClientConfig::new()
+                .access_key("ak")
+                .secret_key("sk")
+                .auth_plugin(CustomAuthPlugin),
+        );
+        let headers = security_proxy.headers_for(&RequestResource::Config {
+            tenant: "ns".to_string(),
+            group: "DEFAULT_GROUP".to_string(),
+        });
+        assert_eq!("ak", headers["Spas-AccessKey"]);
+        assert!(headers.contains_key("Spas-Signature"));
+        assert_eq!("DEFAULT_GROUP", headers["custom-token"]);
+        assert!(security_proxy
+            .headers_for(&RequestResource::None)
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_login_failed() {
+        let auth_plugin = UsernamePasswordAuthPlugin::new("nacos".to_string(), "nacos".to_string());
+        assert!(!auth_plugin.login(&["127.0.0.1:1".to_string()]).await);
+        assert!(auth_plugin.need_refresh());
+    }
+}
@@ -0,0 +1,255 @@
+use crate::api::client_config::ClientConfig;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The interval of refreshing server list from endpoint.
+const REFRESH_SERVER_LIST_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The timeout of fetching server list from endpoint.
+const FETCH_SERVER_LIST_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The default http port of nacos server, if the server list has no port.
+const DEFAULT_SERVER_HTTP_PORT: u32 = 8848;
+
+/// The offset of grpc port from http port.
+const GRPC_PORT_OFFSET: u32 = 1000;
+
+/// The server addresses of a nacos cluster, start at a random one,
+/// rotate to the next one when connecting fails or the stream closes.
+/// Refreshed from endpoint periodically if configured.
+#[derive(Clone)]
+pub(crate) struct ServerListManager {
+    endpoint: Option<String>,
+    servers: Arc<Mutex<Vec<String>>>,
+    index: Arc<AtomicUsize>,
+    started: Arc<AtomicBool>,
+}
+
+impl ServerListManager {
+    pub(crate) fn new(client_config: &ClientConfig) -> Self {
+        let servers = client_config.server_list();
+        let index = rand::random::<usize>() % servers.len();
+        Self {
+            endpoint: client_config.endpoint.clone(),
+            servers: Arc::new(Mutex::new(servers)),
+            index: Arc::new(AtomicUsize::new(index)),
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Fetch the server list from endpoint, then refresh it periodically, only once.
+    pub(crate) async fn start(&self) {
+        let endpoint = match &self.endpoint {
+            None => return,
+            Some(endpoint) => endpoint.clone(),
+        };
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.refresh_server_list(&endpoint).await;
+
+        let server_list = self.clone();
+        let _refresh_thread = std::thread::Builder::new()
+            .name("server-list-refresher".into())
+            .spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_io()
+                    .enable_time()
+                    .build()
+                    .expect("server-list-refresher runtime initialization failed");
+
+                runtime.block_on(async move {
+                    let mut refresh_interval = tokio::time::interval_at(
+                        tokio::time::Instant::now() + REFRESH_SERVER_LIST_INTERVAL,
+                        REFRESH_SERVER_LIST_INTERVAL,
+                    );
+                    loop {
+                        refresh_interval.tick().await;
+                        server_list.refresh_server_list(&endpoint).await;
+                    }
+                });
+            })
+            .expect("server-list-refresher could not spawn thread");
+    }
+
+    /// Refresh the server list, keep the last known one if failed.
+    async fn refresh_server_list(&self, endpoint: &str) {
+        let servers = match fetch_server_list(endpoint).await {
+            Ok(servers) if !servers.is_empty() => servers,
+            Ok(_) => {
+                tracing::warn!(
+                    "empty server list from endpoint {}, keep the last",
+                    endpoint
+                );
+                return;
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "fetch server list from endpoint {} failed, keep the last, {:?}",
+                    endpoint,
+                    err
+                );
+                return;
+            }
+        };
+        loop {
+            let servers_lock = self.servers.try_lock();
+            if let Ok(mut mutex) = servers_lock {
+                if *mutex != servers {
+                    tracing::info!("server list changed, {:?} -> {:?}", *mutex, servers);
+                    *mutex = servers;
+                }
+                break;
+            }
+        }
+    }
+
+    /// All the server addresses.
+    pub(crate) fn servers(&self) -> Vec<String> {
+        loop {
+            let servers_lock = self.servers.try_lock();
+            if let Ok(mutex) = servers_lock {
+                return mutex.clone();
+            }
+        }
+    }
+
+    /// The current server address.
+    pub(crate) fn current_server(&self) -> String {
+        let index = self.index.load(Ordering::Relaxed);
+        self.server_at(index)
+    }
+
+    /// Rotate to the next server address, return it.
+    pub(crate) fn next_server(&self) -> String {
+        let index = self.index.fetch_add(1, Ordering::Relaxed) + 1;
+        self.server_at(index)
+    }
+
+    fn server_at(&self, index: usize) -> String {
+        loop {
+            let servers_lock = self.servers.try_lock();
+            if let Ok(mutex) = servers_lock {
+                return mutex[index % mutex.len()].clone();
+            }
+        }
+    }
+}
+
+/// GET http://{endpoint}/nacos/serverlist
+async fn fetch_server_list(endpoint: &str) -> crate::api::error::Result<Vec<String>> {
+    let endpoint = endpoint.trim_end_matches('/');
+    let url = if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+        format!("{}/nacos/serverlist", endpoint)
+    } else {
+        format!("http://{}/nacos/serverlist", endpoint)
+    };
+    let resp = reqwest::Client::new()
+        .get(url)
+        .timeout(FETCH_SERVER_LIST_TIMEOUT)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(parse_server_list(resp.text().await?.as_str()))
+}
+
+/// One server per line, `ip` or `ip:port` with http port, converted to grpc address.
+fn parse_server_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| match line.rsplit_once(':') {
+            Some((ip, port)) => Some(grpc_server_addr(ip, Some(port.trim().parse().ok()?))),
+            None => Some(grpc_server_addr(line, None)),
+        })
+        .collect()
+}
+
+/// The grpc address of server, by its ip and http port, default 8848.
+pub(crate) fn grpc_server_addr(ip: &str, http_port: Option<u32>) -> String {
+    format!(
+        "{}:{}",
+        ip,
+        http_port.unwrap_or(DEFAULT_SERVER_HTTP_PORT) + GRPC_PORT_OFFSET
+    )
+}
+
+/// The http address of server, by its grpc address, as it is if no port.
+pub(crate) fn http_server_addr(grpc_addr: &str) -> String {
+    match grpc_addr.rsplit_once(':') {
+        Some((ip, port)) => match port.parse::<u32>() {
+            Ok(port) if port > GRPC_PORT_OFFSET => format!("{}:{}", ip, port - GRPC_PORT_OFFSET),
+            _ => grpc_addr.to_string(),
+        },
+        None => grpc_addr.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::client_config::ClientConfig;
+    use crate::common::remote::server_list::{
+        http_server_addr, parse_server_list, ServerListManager,
+    };
+
+    #[test]
+    fn test_rotate_server() {
+        let client_config =
+            ClientConfig::new().server_addr(" 127.0.0.1:9848, 127.0.0.2:9848,,127.0.0.3:9848");
+        let server_list = ServerListManager::new(&client_config);
+        let first = server_list.current_server();
+        assert!(client_config.server_list().contains(&first));
+        let second = server_list.next_server();
+        assert_ne!(first, second);
+        assert_eq!(second, server_list.current_server());
+        assert_ne!(second, server_list.next_server());
+        assert_eq!(first, server_list.next_server());
+
+        let server_list = ServerListManager::new(&ClientConfig::new().server_addr(""));
+        assert_eq!(
+            crate::api::constants::DEFAULT_SERVER_ADDR,
+            server_list.next_server()
+        );
+    }
+
+    #[test]
+    fn test_parse_server_list() {
+        let servers =
+            parse_server_list("127.0.0.1:8848\n\n 127.0.0.2 \n127.0.0.3:x\n127.0.0.4:8849\n");
+        assert_eq!(
+            vec!["127.0.0.1:9848", "127.0.0.2:9848", "127.0.0.4:9849"],
+            servers
+        );
+    }
+
+    #[test]
+    fn test_http_server_addr() {
+        assert_eq!("127.0.0.1:8848", http_server_addr("127.0.0.1:9848"));
+        assert_eq!("127.0.0.1", http_server_addr("127.0.0.1"));
+        assert_eq!("127.0.0.1:x", http_server_addr("127.0.0.1:x"));
+    }
+
+    #[test]
+    fn test_connect_reset_server_addr() {
+        use crate::common::remote::request::server_request::ConnectResetServerRequest;
+        let reset = ConnectResetServerRequest::from(
+            r#"{"requestId":"1","headers":{},"serverIp":"127.0.0.2","serverPort":"8849"}"#,
+        );
+        assert_eq!(Some("127.0.0.2:9849".to_string()), reset.server_addr());
+        let reset = ConnectResetServerRequest::from(r#"{"requestId":"1","headers":{}}"#);
+        assert_eq!(None, reset.server_addr());
+    }
+
+    #[tokio::test]
+    async fn test_keep_server_list_if_endpoint_down() {
+        let server_list = ServerListManager::new(
+            &ClientConfig::new()
+                .server_addr("127.0.0.1:9848")
+                .endpoint("127.0.0.1:1"),
+        );
+        server_list.refresh_server_list("127.0.0.1:1").await;
+        assert_eq!("127.0.0.1:9848", server_list.next_server());
+    }
+}
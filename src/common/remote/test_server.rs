@@ -0,0 +1,63 @@
+use crate::common::remote::response::server_response::ServerCheckServerResponse;
+use crate::common::util::payload_helper;
+use crate::nacos_proto::v2::{
+    create_bi_request_stream, create_request, BiRequestStream, Payload, Request,
+};
+use futures::stream::StreamExt;
+use std::sync::Arc;
+
+/// The connection id given by the test server.
+pub(crate) const TEST_CONNECTION_ID: &str = "test-conn";
+
+/// A local server for the connection tests, answers ServerCheckRequest with the connection id,
+/// and keeps the bi-stream open until the client closes it.
+#[derive(Clone)]
+struct TestServer;
+
+impl Request for TestServer {
+    fn request(
+        &mut self,
+        ctx: grpcio::RpcContext,
+        _req: Payload,
+        sink: grpcio::UnarySink<Payload>,
+    ) {
+        let resp = payload_helper::build_resp_grpc_payload(ServerCheckServerResponse::new(
+            TEST_CONNECTION_ID.to_string(),
+            "".to_string(),
+        ));
+        ctx.spawn(async move {
+            let _ = sink.success(resp).await;
+        });
+    }
+}
+
+impl BiRequestStream for TestServer {
+    fn request_bi_stream(
+        &mut self,
+        ctx: grpcio::RpcContext,
+        mut stream: grpcio::RequestStream<Payload>,
+        sink: grpcio::DuplexSink<Payload>,
+    ) {
+        ctx.spawn(async move {
+            let _sink = sink;
+            while let Some(Ok(_)) = stream.next().await {}
+        });
+    }
+}
+
+/// Start the test server listening on the addr, e.g. 127.0.0.1:0 for any port,
+/// return it with the port, it stops once dropped.
+pub(crate) fn start_test_server(
+    addr: &str,
+    credentials: grpcio::ServerCredentials,
+) -> (grpcio::Server, u16) {
+    let env = Arc::new(grpcio::Environment::new(1));
+    let mut server = grpcio::ServerBuilder::new(env)
+        .register_service(create_request(TestServer))
+        .register_service(create_bi_request_stream(TestServer))
+        .build()
+        .unwrap();
+    let port = server.add_listening_port(addr, credentials).unwrap();
+    server.start();
+    (server, port)
+}
@@ -0,0 +1,214 @@
+use crate::api::config::{ConfigChangeItem, PropertyChangeType};
+use std::collections::BTreeMap;
+
+/// Parse the changed keys between old and new content, by the content_type.
+/// Support properties, yaml and json, others return empty.
+pub(crate) fn parse_change_items(
+    content_type: &str,
+    old_content: &str,
+    new_content: &str,
+) -> Vec<ConfigChangeItem> {
+    let parse: fn(&str) -> Option<BTreeMap<String, String>> =
+        match content_type.to_lowercase().as_str() {
+            "properties" => |c| Some(parse_properties(c)),
+            "yaml" | "yml" => parse_yaml,
+            "json" => parse_json,
+            _ => return Vec::new(),
+        };
+    match (parse(old_content), parse(new_content)) {
+        (Some(old_map), Some(new_map)) => diff(old_map, new_map),
+        _ => {
+            tracing::warn!("parse change items failed, content_type={}", content_type);
+            Vec::new()
+        }
+    }
+}
+
+fn diff(
+    mut old_map: BTreeMap<String, String>,
+    new_map: BTreeMap<String, String>,
+) -> Vec<ConfigChangeItem> {
+    let mut change_items = Vec::new();
+    for (key, new_value) in new_map {
+        match old_map.remove(&key) {
+            None => change_items.push(ConfigChangeItem::new(
+                key,
+                None,
+                Some(new_value),
+                PropertyChangeType::Added,
+            )),
+            Some(old_value) if old_value != new_value => change_items.push(ConfigChangeItem::new(
+                key,
+                Some(old_value),
+                Some(new_value),
+                PropertyChangeType::Modified,
+            )),
+            _ => {}
+        }
+    }
+    for (key, old_value) in old_map {
+        change_items.push(ConfigChangeItem::new(
+            key,
+            Some(old_value),
+            None,
+            PropertyChangeType::Deleted,
+        ));
+    }
+    change_items.sort_by(|a, b| a.get_key().cmp(b.get_key()));
+    change_items
+}
+
+/// Parse properties, `key=value` or `key:value`, `#` and `!` lines are comments,
+/// a line ends with `\` continues on the next line.
+pub(crate) fn parse_properties(content: &str) -> BTreeMap<String, String> {
+    let mut map = BTreeMap::new();
+    let mut logical_line = String::new();
+    for line in content.lines() {
+        let line = line.trim_start();
+        if logical_line.is_empty() && (line.is_empty() || line.starts_with(['#', '!'])) {
+            continue;
+        }
+        if let Some(continued) = line.strip_suffix('\\') {
+            logical_line.push_str(continued);
+            continue;
+        }
+        logical_line.push_str(line);
+        let (key, value) = match logical_line.find(['=', ':']) {
+            Some(idx) => (&logical_line[..idx], &logical_line[idx + 1..]),
+            None => (logical_line.as_str(), ""),
+        };
+        map.insert(key.trim().to_string(), value.trim().to_string());
+        logical_line.clear();
+    }
+    map
+}
+
+fn parse_yaml(content: &str) -> Option<BTreeMap<String, String>> {
+    let mut map = BTreeMap::new();
+    if content.trim().is_empty() {
+        return Some(map);
+    }
+    let value: serde_yaml::Value = serde_yaml::from_str(content).ok()?;
+    flatten_yaml(String::new(), &value, &mut map);
+    Some(map)
+}
+
+fn flatten_yaml(prefix: String, value: &serde_yaml::Value, map: &mut BTreeMap<String, String>) {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => {
+            for (k, v) in mapping {
+                let k = match k {
+                    serde_yaml::Value::String(k) => k.clone(),
+                    other => yaml_scalar_to_string(other),
+                };
+                flatten_yaml(join_key(&prefix, &k), v, map);
+            }
+        }
+        serde_yaml::Value::Sequence(seq) => {
+            for (i, v) in seq.iter().enumerate() {
+                flatten_yaml(format!("{}[{}]", prefix, i), v, map);
+            }
+        }
+        serde_yaml::Value::Tagged(tagged) => flatten_yaml(prefix, &tagged.value, map),
+        scalar => {
+            map.insert(prefix, yaml_scalar_to_string(scalar));
+        }
+    }
+}
+
+fn yaml_scalar_to_string(value: &serde_yaml::Value) -> String {
+    match value {
+        serde_yaml::Value::Null => String::new(),
+        serde_yaml::Value::Bool(b) => b.to_string(),
+        serde_yaml::Value::Number(n) => n.to_string(),
+        serde_yaml::Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other).unwrap_or_default(),
+    }
+}
+
+fn parse_json(content: &str) -> Option<BTreeMap<String, String>> {
+    let mut map = BTreeMap::new();
+    if content.trim().is_empty() {
+        return Some(map);
+    }
+    let value: serde_json::Value = serde_json::from_str(content).ok()?;
+    flatten_json(String::new(), &value, &mut map);
+    Some(map)
+}
+
+fn flatten_json(prefix: String, value: &serde_json::Value, map: &mut BTreeMap<String, String>) {
+    match value {
+        serde_json::Value::Object(object) => {
+            for (k, v) in object {
+                flatten_json(join_key(&prefix, k), v, map);
+            }
+        }
+        serde_json::Value::Array(array) => {
+            for (i, v) in array.iter().enumerate() {
+                flatten_json(format!("{}[{}]", prefix, i), v, map);
+            }
+        }
+        serde_json::Value::Null => {
+            map.insert(prefix, String::new());
+        }
+        serde_json::Value::String(s) => {
+            map.insert(prefix, s.clone());
+        }
+        scalar => {
+            map.insert(prefix, scalar.to_string());
+        }
+    }
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::config::PropertyChangeType;
+    use crate::config::change_parser::parse_change_items;
+
+    #[test]
+    fn test_parse_properties_change_items() {
+        let old = "# comment\na=1\nb=2\nc=3";
+        let new = "a=1\nb=22\nd=4 \\\n 4";
+        let items = parse_change_items("properties", old, new);
+        assert_eq!(3, items.len());
+        assert_eq!("b", items[0].get_key());
+        assert_eq!(PropertyChangeType::Modified, items[0].get_change_type());
+        assert_eq!(Some(&"22".to_string()), items[0].get_new_value());
+        assert_eq!("c", items[1].get_key());
+        assert_eq!(PropertyChangeType::Deleted, items[1].get_change_type());
+        assert_eq!("d", items[2].get_key());
+        assert_eq!(PropertyChangeType::Added, items[2].get_change_type());
+        assert_eq!(Some(&"4 4".to_string()), items[2].get_new_value());
+    }
+
+    #[test]
+    fn test_parse_yaml_change_items() {
+        let old = "a:\n  b: 1\n  c: [x, y]\n";
+        let new = "a:\n  b: 2\n  c: [x]\n";
+        let items = parse_change_items("yaml", old, new);
+        assert_eq!(2, items.len());
+        assert_eq!("a.b", items[0].get_key());
+        assert_eq!(PropertyChangeType::Modified, items[0].get_change_type());
+        assert_eq!("a.c[1]", items[1].get_key());
+        assert_eq!(PropertyChangeType::Deleted, items[1].get_change_type());
+    }
+
+    #[test]
+    fn test_parse_json_change_items() {
+        let items = parse_change_items("json", "", r#"{"a":{"b":true},"c":"s"}"#);
+        assert_eq!(2, items.len());
+        assert_eq!("a.b", items[0].get_key());
+        assert_eq!(Some(&"true".to_string()), items[0].get_new_value());
+        assert_eq!(PropertyChangeType::Added, items[1].get_change_type());
+
+        assert!(parse_change_items("text", "a=1", "a=2").is_empty());
+    }
+}
@@ -21,9 +21,18 @@ impl Request for ConfigBatchListenClientRequest {
     fn get_headers(&self) -> &HashMap<String, String> {
         &self.headers
     }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
     fn get_type_url(&self) -> &String {
         &TYPE_CONFIG_BATCH_LISTEN_CLIENT_REQUEST
     }
+    fn get_resource(&self) -> RequestResource {
+        RequestResource::Config {
+            tenant: String::from(""),
+            group: String::from(""),
+        }
+    }
 }
 
 impl ConfigBatchListenClientRequest {
@@ -93,9 +102,18 @@ impl Request for ConfigQueryClientRequest {
     fn get_headers(&self) -> &HashMap<String, String> {
         &self.headers
     }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
     fn get_type_url(&self) -> &String {
         &TYPE_CONFIG_QUERY_CLIENT_REQUEST
     }
+    fn get_resource(&self) -> RequestResource {
+        RequestResource::Config {
+            tenant: self.tenant.clone(),
+            group: self.group.clone(),
+        }
+    }
 }
 
 impl ConfigQueryClientRequest {
@@ -109,3 +127,132 @@ impl ConfigQueryClientRequest {
         }
     }
 }
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct ConfigPublishClientRequest {
+    requestId: String,
+    /// count be empty.
+    headers: HashMap<String, String>,
+    /// DataId
+    dataId: String,
+    /// Group
+    group: String,
+    /// tenant
+    tenant: String,
+    /// content
+    content: String,
+    /// Cas md5 (prev content's md5)
+    casMd5: Option<String>,
+    /// Addition Map, e.g. type, betaIps, tag
+    additionMap: HashMap<String, String>,
+}
+
+impl Request for ConfigPublishClientRequest {
+    fn get_request_id(&self) -> &String {
+        &self.requestId
+    }
+    fn get_headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
+    fn get_type_url(&self) -> &String {
+        &TYPE_CONFIG_PUBLISH_CLIENT_REQUEST
+    }
+    fn get_resource(&self) -> RequestResource {
+        RequestResource::Config {
+            tenant: self.tenant.clone(),
+            group: self.group.clone(),
+        }
+    }
+}
+
+impl ConfigPublishClientRequest {
+    pub fn new(data_id: String, group: String, tenant: String, content: String) -> Self {
+        ConfigPublishClientRequest {
+            requestId: generate_request_id(),
+            headers: HashMap::new(),
+            dataId: data_id,
+            group,
+            tenant,
+            content,
+            casMd5: None,
+            additionMap: HashMap::new(),
+        }
+    }
+
+    /// Sets the cas_md5.
+    pub fn cas_md5(self, cas_md5: Option<String>) -> Self {
+        ConfigPublishClientRequest {
+            casMd5: cas_md5,
+            ..self
+        }
+    }
+
+    /// Add into additionMap.
+    pub fn add_addition_param(mut self, key: impl Into<String>, val: impl Into<String>) -> Self {
+        self.additionMap.insert(key.into(), val.into());
+        self
+    }
+
+    /// Add into additionMap.
+    pub fn add_addition_params(mut self, addition_params: HashMap<String, String>) -> Self {
+        self.additionMap.extend(addition_params.into_iter());
+        self
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct ConfigRemoveClientRequest {
+    requestId: String,
+    /// count be empty.
+    headers: HashMap<String, String>,
+    /// DataId
+    dataId: String,
+    /// Group
+    group: String,
+    /// tenant
+    tenant: String,
+    /// tag
+    tag: Option<String>,
+}
+
+impl Request for ConfigRemoveClientRequest {
+    fn get_request_id(&self) -> &String {
+        &self.requestId
+    }
+    fn get_headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
+    fn get_type_url(&self) -> &String {
+        &TYPE_CONFIG_REMOVE_CLIENT_REQUEST
+    }
+    fn get_resource(&self) -> RequestResource {
+        RequestResource::Config {
+            tenant: self.tenant.clone(),
+            group: self.group.clone(),
+        }
+    }
+}
+
+impl ConfigRemoveClientRequest {
+    pub fn new(data_id: String, group: String, tenant: String) -> Self {
+        ConfigRemoveClientRequest {
+            requestId: generate_request_id(),
+            headers: HashMap::new(),
+            dataId: data_id,
+            group,
+            tenant,
+            tag: None,
+        }
+    }
+
+    /// Sets the tag.
+    pub fn tag(self, tag: Option<String>) -> Self {
+        ConfigRemoveClientRequest { tag, ..self }
+    }
+}
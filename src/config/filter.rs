@@ -0,0 +1,152 @@
+use crate::api::config::{ConfigFilter, ConfigFilterData};
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyInit};
+use base64::Engine;
+use std::sync::Arc;
+
+/// The chain of config filters, applied in order on publish, in reverse order on query.
+#[derive(Clone, Default)]
+pub(crate) struct ConfigFilterChain {
+    config_filters: Vec<Arc<dyn ConfigFilter>>,
+}
+
+impl ConfigFilterChain {
+    pub(crate) fn new(config_filters: Vec<Arc<dyn ConfigFilter>>) -> Self {
+        Self { config_filters }
+    }
+
+    pub(crate) fn filter_publish(
+        &self,
+        config: &mut ConfigFilterData,
+    ) -> crate::api::error::Result<()> {
+        for config_filter in self.config_filters.iter() {
+            config_filter.filter_publish(config)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn filter_query(
+        &self,
+        config: &mut ConfigFilterData,
+    ) -> crate::api::error::Result<()> {
+        for config_filter in self.config_filters.iter().rev() {
+            config_filter.filter_query(config)?;
+        }
+        Ok(())
+    }
+}
+
+/// AES-128 ECB with PKCS7 padding, return the base64 of encrypted content.
+pub(crate) fn aes_encrypt(data_key: &[u8], content: &str) -> crate::api::error::Result<String> {
+    let encryptor = ecb::Encryptor::<aes::Aes128>::new_from_slice(data_key)
+        .map_err(|_| invalid_data_key(data_key))?;
+    let encrypted = encryptor.encrypt_padded_vec_mut::<Pkcs7>(content.as_bytes());
+    Ok(base64::engine::general_purpose::STANDARD.encode(encrypted))
+}
+
+/// Decrypt the base64 of content encrypted by `aes_encrypt`.
+pub(crate) fn aes_decrypt(data_key: &[u8], content: &str) -> crate::api::error::Result<String> {
+    let decryptor = ecb::Decryptor::<aes::Aes128>::new_from_slice(data_key)
+        .map_err(|_| invalid_data_key(data_key))?;
+    let encrypted = base64::engine::general_purpose::STANDARD
+        .decode(content.trim())
+        .map_err(|err| {
+            crate::api::error::Error::ConfigFilter(format!("invalid encrypted content, {}", err))
+        })?;
+    let decrypted = decryptor
+        .decrypt_padded_vec_mut::<Pkcs7>(&encrypted)
+        .map_err(|_| {
+            crate::api::error::Error::ConfigFilter("decrypt content failed".to_string())
+        })?;
+    String::from_utf8(decrypted).map_err(|err| {
+        crate::api::error::Error::ConfigFilter(format!("decrypted content is not utf8, {}", err))
+    })
+}
+
+fn invalid_data_key(data_key: &[u8]) -> crate::api::error::Error {
+    crate::api::error::Error::ConfigFilter(format!(
+        "invalid data key length {}, expect 16",
+        data_key.len()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::config::{AesDataKeyFilter, ConfigFilter, ConfigFilterData, KmsClient};
+    use crate::config::filter::{aes_decrypt, aes_encrypt, ConfigFilterChain};
+    use std::sync::Arc;
+
+    /// Encrypted data key is the plain one in hex, only for test.
+    struct TestKmsClient;
+
+    impl KmsClient for TestKmsClient {
+        fn generate_data_key(
+            &self,
+            _data_id: &str,
+        ) -> crate::api::error::Result<(Vec<u8>, String)> {
+            let data_key = b"0123456789abcdef".to_vec();
+            let encrypted_data_key = data_key.iter().map(|b| format!("{:02x}", b)).collect();
+            Ok((data_key, encrypted_data_key))
+        }
+
+        fn decrypt_data_key(&self, encrypted_data_key: &str) -> crate::api::error::Result<Vec<u8>> {
+            Ok((0..encrypted_data_key.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&encrypted_data_key[i..i + 2], 16).unwrap())
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_aes() {
+        let data_key = b"0123456789abcdef";
+        assert_eq!(
+            "7oXlNccx3e7Xdg/Mjh1mZA==",
+            aes_encrypt(data_key, "k=v").unwrap()
+        );
+        assert_eq!(
+            "k=v",
+            aes_decrypt(data_key, "7oXlNccx3e7Xdg/Mjh1mZA==").unwrap()
+        );
+        assert!(aes_encrypt(b"short", "k=v").is_err());
+        assert!(aes_decrypt(data_key, "not base64!").is_err());
+    }
+
+    #[test]
+    fn test_aes_data_key_filter() {
+        let chain = ConfigFilterChain::new(vec![Arc::new(AesDataKeyFilter::new(TestKmsClient))]);
+        let mut config = ConfigFilterData {
+            data_id: "cipher-app.properties".to_string(),
+            group: "DEFAULT_GROUP".to_string(),
+            content: "k=v".to_string(),
+            ..Default::default()
+        };
+        chain.filter_publish(&mut config).unwrap();
+        assert_eq!("7oXlNccx3e7Xdg/Mjh1mZA==", config.content);
+        assert_eq!(
+            Some("30313233343536373839616263646566".to_string()),
+            config.encrypted_data_key
+        );
+        chain.filter_query(&mut config).unwrap();
+        assert_eq!("k=v", config.content);
+
+        // not cipher- prefixed, as it is.
+        let mut plain = ConfigFilterData {
+            data_id: "app.properties".to_string(),
+            content: "k=v".to_string(),
+            ..Default::default()
+        };
+        chain.filter_publish(&mut plain).unwrap();
+        assert_eq!("k=v", plain.content);
+        assert_eq!(None, plain.encrypted_data_key);
+
+        // no data key to decrypt.
+        let mut no_data_key = ConfigFilterData {
+            data_id: "cipher-app.properties".to_string(),
+            content: "7oXlNccx3e7Xdg/Mjh1mZA==".to_string(),
+            ..Default::default()
+        };
+        assert!(AesDataKeyFilter::new(TestKmsClient)
+            .filter_query(&mut no_data_key)
+            .is_err());
+    }
+}
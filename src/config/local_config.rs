@@ -0,0 +1,179 @@
+use std::path::PathBuf;
+
+const SNAPSHOT_DIR: &str = "snapshot";
+const FAILOVER_DIR: &str = "failover";
+const ENCRYPTED_DATA_KEY_DIR: &str = "encrypted-data-key";
+const CONTENT_TYPE_DIR: &str = "content-type";
+
+/// Local config info, learn from LocalConfigInfoProcessor of Java client.
+/// - snapshot: every successfully fetched config, used when server is unreachable.
+/// - failover: managed by operator, always takes precedence over the server.
+///
+/// Files are keyed by `util::group_key`, the encrypted data keys of them are under
+/// `encrypted-data-key/snapshot` and `encrypted-data-key/failover`,
+/// the content types of snapshot are under `content-type/snapshot`.
+#[derive(Clone)]
+pub(crate) struct LocalConfigInfoProcessor {
+    snapshot_dir: PathBuf,
+    failover_dir: PathBuf,
+    encrypted_data_key_snapshot_dir: PathBuf,
+    encrypted_data_key_failover_dir: PathBuf,
+    content_type_snapshot_dir: PathBuf,
+}
+
+impl LocalConfigInfoProcessor {
+    pub(crate) fn new(config_cache_dir: &str) -> Self {
+        let cache_dir = PathBuf::from(config_cache_dir);
+        let encrypted_data_key_dir = cache_dir.join(ENCRYPTED_DATA_KEY_DIR);
+        Self {
+            snapshot_dir: cache_dir.join(SNAPSHOT_DIR),
+            failover_dir: cache_dir.join(FAILOVER_DIR),
+            encrypted_data_key_snapshot_dir: encrypted_data_key_dir.join(SNAPSHOT_DIR),
+            encrypted_data_key_failover_dir: encrypted_data_key_dir.join(FAILOVER_DIR),
+            content_type_snapshot_dir: cache_dir.join(CONTENT_TYPE_DIR).join(SNAPSHOT_DIR),
+        }
+    }
+
+    /// Get the failover config content.
+    pub(crate) fn get_failover(&self, group_key: &String) -> Option<String> {
+        Self::read_file(self.failover_dir.join(group_key))
+    }
+
+    /// Get the snapshot config content.
+    pub(crate) fn get_snapshot(&self, group_key: &String) -> Option<String> {
+        Self::read_file(self.snapshot_dir.join(group_key))
+    }
+
+    /// Save the snapshot config content.
+    pub(crate) fn save_snapshot(&self, group_key: &String, content: &String) {
+        Self::write_file(&self.snapshot_dir, group_key, content);
+    }
+
+    /// Remove the snapshot config, e.g. config was removed from server.
+    pub(crate) fn remove_snapshot(&self, group_key: &String) {
+        Self::remove_file(self.snapshot_dir.join(group_key));
+    }
+
+    /// Get the encrypted data key of failover config.
+    pub(crate) fn get_encrypted_data_key_failover(&self, group_key: &String) -> Option<String> {
+        Self::read_file(self.encrypted_data_key_failover_dir.join(group_key))
+    }
+
+    /// Get the encrypted data key of snapshot config.
+    pub(crate) fn get_encrypted_data_key_snapshot(&self, group_key: &String) -> Option<String> {
+        Self::read_file(self.encrypted_data_key_snapshot_dir.join(group_key))
+    }
+
+    /// Save the encrypted data key of snapshot config, remove it if absent.
+    pub(crate) fn save_encrypted_data_key_snapshot(
+        &self,
+        group_key: &String,
+        encrypted_data_key: Option<&String>,
+    ) {
+        match encrypted_data_key.filter(|k| !k.is_empty()) {
+            Some(encrypted_data_key) => Self::write_file(
+                &self.encrypted_data_key_snapshot_dir,
+                group_key,
+                encrypted_data_key,
+            ),
+            None => Self::remove_file(self.encrypted_data_key_snapshot_dir.join(group_key)),
+        }
+    }
+
+    /// Get the content type of snapshot config.
+    pub(crate) fn get_content_type_snapshot(&self, group_key: &String) -> Option<String> {
+        Self::read_file(self.content_type_snapshot_dir.join(group_key))
+    }
+
+    /// Save the content type of snapshot config, remove it if absent.
+    pub(crate) fn save_content_type_snapshot(
+        &self,
+        group_key: &String,
+        content_type: Option<&String>,
+    ) {
+        match content_type.filter(|t| !t.is_empty()) {
+            Some(content_type) => {
+                Self::write_file(&self.content_type_snapshot_dir, group_key, content_type)
+            }
+            None => Self::remove_file(self.content_type_snapshot_dir.join(group_key)),
+        }
+    }
+
+    fn write_file(dir: &PathBuf, group_key: &String, content: &String) {
+        if let Err(err) = std::fs::create_dir_all(dir) {
+            tracing::warn!("create snapshot dir {:?} failed, {}", dir, err);
+            return;
+        }
+        let file = dir.join(group_key);
+        if let Err(err) = std::fs::write(&file, content) {
+            tracing::warn!("save snapshot {:?} failed, {}", file, err);
+        }
+    }
+
+    fn remove_file(file: PathBuf) {
+        if file.exists() {
+            if let Err(err) = std::fs::remove_file(&file) {
+                tracing::warn!("remove snapshot {:?} failed, {}", file, err);
+            }
+        }
+    }
+
+    fn read_file(file: PathBuf) -> Option<String> {
+        if !file.is_file() {
+            return None;
+        }
+        match std::fs::read_to_string(&file) {
+            Ok(content) => Some(content),
+            Err(err) => {
+                tracing::warn!("read local config {:?} failed, {}", file, err);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::local_config::LocalConfigInfoProcessor;
+    use crate::config::util;
+
+    #[test]
+    fn test_snapshot_and_failover() {
+        let cache_dir = std::env::temp_dir().join(format!("nacos-config-{}", std::process::id()));
+        let processor = LocalConfigInfoProcessor::new(cache_dir.to_str().unwrap());
+        let group_key = util::group_key(&"d".to_string(), &"g".to_string(), &"t".to_string());
+
+        assert_eq!(None, processor.get_snapshot(&group_key));
+        processor.save_snapshot(&group_key, &"k=v".to_string());
+        assert_eq!(Some("k=v".to_string()), processor.get_snapshot(&group_key));
+        processor.remove_snapshot(&group_key);
+        assert_eq!(None, processor.get_snapshot(&group_key));
+
+        processor.save_encrypted_data_key_snapshot(&group_key, Some(&"key".to_string()));
+        assert_eq!(
+            Some("key".to_string()),
+            processor.get_encrypted_data_key_snapshot(&group_key)
+        );
+        processor.save_encrypted_data_key_snapshot(&group_key, None);
+        assert_eq!(None, processor.get_encrypted_data_key_snapshot(&group_key));
+        assert_eq!(None, processor.get_encrypted_data_key_failover(&group_key));
+
+        processor.save_content_type_snapshot(&group_key, Some(&"json".to_string()));
+        assert_eq!(
+            Some("json".to_string()),
+            processor.get_content_type_snapshot(&group_key)
+        );
+        processor.save_content_type_snapshot(&group_key, None);
+        assert_eq!(None, processor.get_content_type_snapshot(&group_key));
+
+        assert_eq!(None, processor.get_failover(&group_key));
+        std::fs::create_dir_all(cache_dir.join("failover")).unwrap();
+        std::fs::write(cache_dir.join("failover").join(&group_key), "k=failover").unwrap();
+        assert_eq!(
+            Some("k=failover".to_string()),
+            processor.get_failover(&group_key)
+        );
+
+        std::fs::remove_dir_all(cache_dir).unwrap();
+    }
+}
@@ -1,98 +1,137 @@
+mod change_parser;
 mod client_request;
 mod client_response;
+pub(crate) mod filter;
+mod local_config;
 mod server_request;
 mod server_response;
+pub(crate) mod typed_config;
 mod util;
 mod worker;
 
 use crate::api::client_config::ClientConfig;
-use crate::api::config::ConfigService;
+use crate::api::config::{
+    ConfigFilter, ConfigFilterData, ConfigResponse, ConfigService, ListenerId,
+};
 use crate::common::remote::conn::Connection;
-use crate::common::remote::request::server_request::*;
+use crate::common::remote::remote_client::{start_remote_client, ServerRequestHandler};
 use crate::common::remote::request::*;
-use crate::common::remote::response::client_response::*;
-use crate::common::util::payload_helper;
+use crate::common::remote::response::Response;
 use crate::common::util::payload_helper::PayloadInner;
 use crate::config::client_request::*;
 use crate::config::client_response::*;
+use crate::config::filter::ConfigFilterChain;
 use crate::config::server_request::*;
 use crate::config::server_response::*;
-use crate::config::worker::ConfigWorker;
+use crate::config::worker::{ConfigListener, ConfigWorker};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The interval of list ensure cache-data newest.
+const LIST_ENSURE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// The key of content type in additionMap of ConfigPublishRequest.
+const ADDITION_KEY_TYPE: &str = "type";
+/// The key of beta ips in additionMap of ConfigPublishRequest.
+const ADDITION_KEY_BETA_IPS: &str = "betaIps";
+/// The key of encrypted data key in additionMap of ConfigPublishRequest.
+const ADDITION_KEY_ENCRYPTED_DATA_KEY: &str = "encryptedDataKey";
 
 pub(crate) struct NacosConfigService {
     client_config: ClientConfig,
     connection: Connection,
     /// config client worker
     client_worker: ConfigWorker,
+    /// applied on publish, and on query and notify by client_worker
+    config_filter_chain: ConfigFilterChain,
 }
 
 impl NacosConfigService {
-    pub fn new(client_config: ClientConfig) -> Self {
+    pub fn new(client_config: ClientConfig, config_filters: Vec<Arc<dyn ConfigFilter>>) -> Self {
         let connection = Connection::new(client_config.clone());
-        let client_worker = ConfigWorker::new(client_config.clone());
+        let config_filter_chain = ConfigFilterChain::new(config_filters);
+        let client_worker = ConfigWorker::new(client_config.clone(), config_filter_chain.clone());
         Self {
             client_config,
             connection,
             client_worker,
+            config_filter_chain,
         }
     }
 
     /// start Once
     pub(crate) async fn start(&mut self) {
-        self.connection.connect().await;
-
-        let mut conn = self.connection.clone();
-        let mut client_worker = self.client_worker.clone();
-
-        let _conn_thread = std::thread::Builder::new()
-            .name("config-remote-client".into())
-            .spawn(|| {
-                let runtime = tokio::runtime::Builder::new_current_thread()
-                    .enable_io()
-                    .enable_time()
-                    .build()
-                    .expect("config-remote-client runtime initialization failed");
-
-                runtime.block_on(async move {
-                    let (server_req_payload_tx, mut server_req_payload_rx) = tokio::sync::mpsc::channel(128);
-                    loop {
-                        tokio::select! { biased;
-                            // deal with next_server_req_payload, basic conn interaction logic.
-                            server_req_payload = conn.next_server_req_payload() => {
-                                let payload_inner = payload_helper::covert_payload(server_req_payload);
-                                if TYPE_CLIENT_DETECTION_SERVER_REQUEST.eq(&payload_inner.type_url) {
-                                    let de = ClientDetectionServerRequest::from(payload_inner.body_str.as_str()).headers(payload_inner.headers);
-                                    conn.reply_client_resp(ClientDetectionClientResponse::new(de.get_request_id().clone())).await;
-                                } else if TYPE_CONNECT_RESET_SERVER_REQUEST.eq(&payload_inner.type_url) {
-                                    let de = ConnectResetServerRequest::from(payload_inner.body_str.as_str()).headers(payload_inner.headers);
-                                    conn.reply_client_resp(ConnectResetClientResponse::new(de.get_request_id().clone())).await;
-                                    // todo reset connection
-                                } else {
-                                    // publish a server_req_payload, server_req_payload_rx receive it once.
-                                    if let Err(_) = server_req_payload_tx.send(payload_inner).await {
-                                        tracing::error!("receiver dropped")
-                                    }
-                                }
-                            },
-                            // receive a server_req from server_req_payload_tx
-                            receive_server_req = server_req_payload_rx.recv() => {
-                                Self::deal_extra_server_req(&mut client_worker, &mut conn, receive_server_req.unwrap()).await
-                            },
-                        }
-                    }
-                });
-            })
-            .expect("config-remote-client could not spawn thread");
+        // not block when the whole cluster is down, the bi-stream loop keeps reconnecting.
+        if !self.connection.connect_once().await {
+            tracing::warn!("no server available now, keep connecting in background");
+        }
+
+        start_remote_client(
+            "config-remote-client",
+            self.connection.clone(),
+            ConfigRemoteHandler {
+                client_worker: self.client_worker.clone(),
+                need_redo: false,
+            },
+        );
 
         // sleep 6ms, Make sure the link is established.
         tokio::time::sleep(std::time::Duration::from_millis(6)).await
     }
 
-    async fn deal_extra_server_req(
-        client_worker: &mut ConfigWorker,
-        conn: &mut Connection,
-        payload_inner: PayloadInner,
-    ) {
+    async fn do_add_listener(
+        &mut self,
+        data_id: String,
+        group: String,
+        listener: ConfigListener,
+    ) -> crate::api::error::Result<ListenerId> {
+        let listener_id = self.client_worker.add_listener(
+            data_id.clone(),
+            group.clone(),
+            self.client_config.namespace.clone(),
+            listener,
+        );
+        let req = ConfigBatchListenClientRequest::new(true).add_config_listen_context(
+            ConfigListenContext::new(
+                data_id.clone(),
+                group.clone(),
+                self.client_config.namespace.clone(),
+                String::from(""),
+            ),
+        );
+        if let Err(err) = self.connection.send_client_req(req).await {
+            // the caller never sees the listener_id, so it could not be removed later.
+            self.client_worker.remove_listener(
+                data_id,
+                group,
+                self.client_config.namespace.clone(),
+                listener_id,
+            );
+            return Err(err);
+        }
+        // the md5 of new cache-data is empty, otherwise regarded as changed later.
+        self.client_worker
+            .init_cache_data(
+                &mut self.connection,
+                data_id,
+                group,
+                self.client_config.namespace.clone(),
+            )
+            .await;
+        Ok(listener_id)
+    }
+}
+
+/// Deal with the config change pushes, list-ensure and redo in the remote client loop.
+struct ConfigRemoteHandler {
+    client_worker: ConfigWorker,
+    /// listen all cache-data again once reconnected, until it succeeds.
+    need_redo: bool,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ServerRequestHandler for ConfigRemoteHandler {
+    async fn handle_server_request(&mut self, conn: &mut Connection, payload_inner: PayloadInner) {
         if TYPE_CONFIG_CHANGE_NOTIFY_SERVER_REQUEST.eq(&payload_inner.type_url) {
             let server_req = ConfigChangeNotifyServerRequest::from(payload_inner.body_str.as_str())
                 .headers(payload_inner.headers);
@@ -104,15 +143,18 @@ impl NacosConfigService {
                 &server_req.group,
                 req_tenant.clone()
             );
-            // notify config change
-            client_worker.notify_config_change(
-                server_req.dataId.to_string(),
-                server_req.group.to_string(),
-                req_tenant.clone(),
-            );
             // reply ConfigChangeNotifyClientResponse for ConfigChangeNotifyServerRequest
             conn.reply_client_resp(ConfigChangeNotifyClientResponse::new(server_req_id))
                 .await;
+            // notify config change
+            self.client_worker
+                .notify_config_change(
+                    conn,
+                    server_req.dataId.to_string(),
+                    server_req.group.to_string(),
+                    req_tenant.clone(),
+                )
+                .await;
         } else {
             tracing::warn!(
                 "unknown receive type_url={}, maybe sdk have to upgrade!",
@@ -120,47 +162,221 @@ impl NacosConfigService {
             );
         }
     }
+
+    async fn redo(&mut self, conn: &mut Connection, reconnected: bool) {
+        if reconnected {
+            tracing::info!("reconnected, redo config listen contexts");
+            self.need_redo = true;
+        }
+        if self.need_redo {
+            self.need_redo = !self.client_worker.list_ensure_cache_data_newest(conn).await;
+        }
+    }
+
+    fn interval(&self) -> Option<std::time::Duration> {
+        Some(LIST_ENSURE_INTERVAL)
+    }
+
+    /// List ensure cache-data newest periodically.
+    async fn on_interval(&mut self, conn: &mut Connection) {
+        self.client_worker.list_ensure_cache_data_newest(conn).await;
+    }
 }
 
+#[async_trait::async_trait]
 impl ConfigService for NacosConfigService {
-    fn get_config(
+    async fn get_config(
         &mut self,
         data_id: String,
         group: String,
-        _timeout_ms: u64,
+        timeout_ms: u64,
     ) -> crate::api::error::Result<String> {
+        self.get_config_response(data_id, group, timeout_ms)
+            .await
+            .map(|config_resp| config_resp.get_content().clone())
+    }
+
+    async fn get_config_response(
+        &mut self,
+        data_id: String,
+        group: String,
+        timeout_ms: u64,
+    ) -> crate::api::error::Result<ConfigResponse> {
         let tenant = self.client_config.namespace.clone();
-        let req = ConfigQueryClientRequest::new(data_id, group, tenant);
-        let req_payload = payload_helper::build_req_grpc_payload(req);
-        let resp = self.connection.get_client()?.request(&req_payload)?;
-        let payload_inner = payload_helper::covert_payload(resp);
-        let config_resp = ConfigQueryServerResponse::from(payload_inner.body_str.as_str());
-        Ok(String::from(config_resp.get_content()))
+        self.client_worker
+            .get_config(
+                &mut self.connection,
+                data_id,
+                group,
+                tenant,
+                std::time::Duration::from_millis(timeout_ms),
+            )
+            .await
     }
 
-    fn add_listener(
+    async fn add_listener(
         &mut self,
         data_id: String,
         group: String,
         listener: Box<crate::api::config::ConfigChangeListener>,
+    ) -> crate::api::error::Result<ListenerId> {
+        self.do_add_listener(data_id, group, ConfigListener::Response(listener))
+            .await
+    }
+
+    async fn add_change_event_listener(
+        &mut self,
+        data_id: String,
+        group: String,
+        listener: Box<crate::api::config::ConfigChangeEventListener>,
+    ) -> crate::api::error::Result<ListenerId> {
+        self.do_add_listener(data_id, group, ConfigListener::ChangeEvent(listener))
+            .await
+    }
+
+    async fn watch(
+        &mut self,
+        data_id: String,
+        group: String,
+        timeout_ms: u64,
+    ) -> crate::api::error::Result<(ListenerId, tokio::sync::watch::Receiver<ConfigResponse>)> {
+        let config_resp = self
+            .get_config_response(data_id.clone(), group.clone(), timeout_ms)
+            .await?;
+        let (sender, receiver) = tokio::sync::watch::channel(config_resp);
+        let listener_id = self
+            .do_add_listener(data_id, group, ConfigListener::Watch(sender))
+            .await?;
+        Ok((listener_id, receiver))
+    }
+
+    async fn remove_listener(
+        &mut self,
+        data_id: String,
+        group: String,
+        listener_id: ListenerId,
     ) -> crate::api::error::Result<()> {
-        self.client_worker.add_listener(
+        let tenant = self.client_config.namespace.clone();
+        let no_listener = self.client_worker.remove_listener(
             data_id.clone(),
             group.clone(),
-            self.client_config.namespace.clone(),
-            listener,
+            tenant.clone(),
+            listener_id,
         );
-        // todo 抽离到统一的发起地方，并取得结果
-        let req = ConfigBatchListenClientRequest::new(true).add_config_listen_context(
-            ConfigListenContext::new(
-                data_id.clone(),
-                group.clone(),
-                self.client_config.namespace.clone(),
-                String::from(""),
-            ),
+        if !no_listener {
+            return Ok(());
+        }
+        // no listener left, tell server stop notifying.
+        let req = ConfigBatchListenClientRequest::new(false).add_config_listen_context(
+            ConfigListenContext::new(data_id, group, tenant, String::from("")),
         );
-        let req_payload = payload_helper::build_req_grpc_payload(req);
-        let _resp_payload = self.connection.get_client()?.request(&req_payload)?;
+        let _payload_inner = self.connection.send_client_req(req).await?;
+        Ok(())
+    }
+
+    async fn publish_config(
+        &mut self,
+        data_id: String,
+        group: String,
+        content: String,
+        content_type: Option<String>,
+    ) -> crate::api::error::Result<()> {
+        self.publish_config_param(data_id, group, content, content_type, None, HashMap::new())
+            .await
+    }
+
+    async fn publish_config_cas(
+        &mut self,
+        data_id: String,
+        group: String,
+        content: String,
+        content_type: Option<String>,
+        cas_md5: String,
+    ) -> crate::api::error::Result<()> {
+        self.publish_config_param(
+            data_id,
+            group,
+            content,
+            content_type,
+            Some(cas_md5),
+            HashMap::new(),
+        )
+        .await
+    }
+
+    async fn publish_config_beta(
+        &mut self,
+        data_id: String,
+        group: String,
+        content: String,
+        content_type: Option<String>,
+        beta_ips: String,
+    ) -> crate::api::error::Result<()> {
+        let mut params = HashMap::with_capacity(1);
+        params.insert(ADDITION_KEY_BETA_IPS.to_string(), beta_ips);
+        self.publish_config_param(data_id, group, content, content_type, None, params)
+            .await
+    }
+
+    async fn publish_config_param(
+        &mut self,
+        data_id: String,
+        group: String,
+        content: String,
+        content_type: Option<String>,
+        cas_md5: Option<String>,
+        params: HashMap<String, String>,
+    ) -> crate::api::error::Result<()> {
+        let mut config = ConfigFilterData {
+            data_id,
+            group,
+            namespace: self.client_config.namespace.clone(),
+            content,
+            encrypted_data_key: None,
+        };
+        self.config_filter_chain.filter_publish(&mut config)?;
+        let mut req = ConfigPublishClientRequest::new(
+            config.data_id,
+            config.group,
+            config.namespace,
+            config.content,
+        )
+        .cas_md5(cas_md5)
+        .add_addition_params(params);
+        if let Some(content_type) = content_type {
+            req = req.add_addition_param(ADDITION_KEY_TYPE, content_type);
+        }
+        if let Some(encrypted_data_key) = config.encrypted_data_key {
+            req = req.add_addition_param(ADDITION_KEY_ENCRYPTED_DATA_KEY, encrypted_data_key);
+        }
+        let payload_inner = self.connection.send_client_req(req).await?;
+        let publish_resp = ConfigPublishServerResponse::try_from(payload_inner.body_str.as_str())?;
+        if !publish_resp.is_success() {
+            return Err(crate::api::error::Error::ErrResult(format!(
+                "publish config failed, error_code={},message={}",
+                publish_resp.get_error_code(),
+                publish_resp.get_message().unwrap_or(&"".to_string())
+            )));
+        }
+        Ok(())
+    }
+
+    async fn remove_config(
+        &mut self,
+        data_id: String,
+        group: String,
+    ) -> crate::api::error::Result<()> {
+        let tenant = self.client_config.namespace.clone();
+        let req = ConfigRemoveClientRequest::new(data_id, group, tenant);
+        let payload_inner = self.connection.send_client_req(req).await?;
+        let remove_resp = ConfigRemoveServerResponse::try_from(payload_inner.body_str.as_str())?;
+        if !remove_resp.is_success() {
+            return Err(crate::api::error::Error::ErrResult(format!(
+                "remove config failed, error_code={},message={}",
+                remove_resp.get_error_code(),
+                remove_resp.get_message().unwrap_or(&"".to_string())
+            )));
+        }
         Ok(())
     }
 }
@@ -182,22 +398,26 @@ mod tests {
             ClientConfig::new()
                 .server_addr("0.0.0.0:9848".to_string())
                 .app_name("test-app-name"),
+            Vec::new(),
         );
         config_service.start().await;
-        let config =
-            config_service.get_config("hongwen.properties".to_string(), "LOVE".to_string(), 3000);
+        let config = config_service
+            .get_config("hongwen.properties".to_string(), "LOVE".to_string(), 3000)
+            .await;
         match config {
             Ok(config) => tracing::info!("get the config {}", config),
             Err(err) => tracing::error!("get the config {:?}", err),
         }
 
-        let _listen = config_service.add_listener(
-            "hongwen.properties".to_string(),
-            "LOVE".to_string(),
-            Box::new(|config_resp| {
-                tracing::info!("listen the config {}", config_resp.get_content());
-            }),
-        );
+        let _listen = config_service
+            .add_listener(
+                "hongwen.properties".to_string(),
+                "LOVE".to_string(),
+                Box::new(|config_resp| {
+                    tracing::info!("listen the config {}", config_resp.get_content());
+                }),
+            )
+            .await;
         match _listen {
             Ok(_) => tracing::info!("listening the config"),
             Err(err) => tracing::error!("listen config error {:?}", err),
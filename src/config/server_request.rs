@@ -21,6 +21,9 @@ impl Request for ConfigChangeNotifyServerRequest {
     fn get_headers(&self) -> &HashMap<String, String> {
         &self.headers
     }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
     fn get_type_url(&self) -> &String {
         &TYPE_CONFIG_CHANGE_NOTIFY_SERVER_REQUEST
     }
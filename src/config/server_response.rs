@@ -49,6 +49,14 @@ impl ConfigChangeBatchListenServerResponse {
     }
 }
 
+impl TryFrom<&str> for ConfigChangeBatchListenServerResponse {
+    type Error = crate::api::error::Error;
+
+    fn try_from(json_str: &str) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(json_str)?)
+    }
+}
+
 /// The Context of config changed.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct ConfigContext {
@@ -56,7 +64,8 @@ pub(crate) struct ConfigContext {
     pub(crate) dataId: String,
     /// Group
     pub(crate) group: String,
-    /// tenant
+    /// tenant, maybe absent when public namespace.
+    #[serde(default)]
     pub(crate) tenant: String,
 }
 
@@ -71,15 +80,21 @@ pub(crate) struct ConfigQueryServerResponse {
     message: Option<String>,
 
     /// json, properties, txt, html, xml, ...
+    #[serde(default)]
     contentType: String,
+    /// maybe absent when config not found.
+    #[serde(default)]
     content: String,
+    #[serde(default)]
     md5: String,
     /// whether content was encrypted with encryptedDataKey.
     encryptedDataKey: Option<String>,
 
     /// now is useless.
     tag: Option<String>,
+    #[serde(default)]
     lastModified: i64,
+    #[serde(default)]
     beta: bool,
 }
 
@@ -101,7 +116,7 @@ impl Response for ConfigQueryServerResponse {
     }
 
     fn get_type_url(&self) -> &String {
-        &TYPE_CONFIG_CHANGE_BATCH_LISTEN_RESPONSE
+        &TYPE_CONFIG_QUERY_SERVER_RESPONSE
     }
 }
 
@@ -124,11 +139,179 @@ impl ConfigQueryServerResponse {
     pub fn get_encrypted_Data_Key(&self) -> Option<&String> {
         Option::from(&self.encryptedDataKey)
     }
+    pub fn get_last_modified(&self) -> i64 {
+        self.lastModified
+    }
+
+    /// Sets the content, e.g. decrypted by config filters.
+    pub(crate) fn content(self, content: String) -> Self {
+        ConfigQueryServerResponse { content, ..self }
+    }
+}
+
+impl TryFrom<&str> for ConfigQueryServerResponse {
+    type Error = crate::api::error::Error;
+
+    fn try_from(json_str: &str) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(json_str)?)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct ConfigPublishServerResponse {
+    requestId: Option<String>,
+    resultCode: ResponseCode,
+    errorCode: u32,
+    message: Option<String>,
+}
+
+impl Response for ConfigPublishServerResponse {
+    fn is_success(&self) -> bool {
+        ResponseCode::Ok == self.resultCode
+    }
+
+    fn get_request_id(&self) -> Option<&String> {
+        Option::from(&self.requestId)
+    }
+
+    fn get_message(&self) -> Option<&String> {
+        Option::from(&self.message)
+    }
+
+    fn get_error_code(&self) -> u32 {
+        self.errorCode
+    }
+
+    fn get_type_url(&self) -> &String {
+        &TYPE_CONFIG_PUBLISH_SERVER_RESPONSE
+    }
+}
+
+impl TryFrom<&str> for ConfigPublishServerResponse {
+    type Error = crate::api::error::Error;
+
+    fn try_from(json_str: &str) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(json_str)?)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct ConfigRemoveServerResponse {
+    requestId: Option<String>,
+    resultCode: ResponseCode,
+    errorCode: u32,
+    message: Option<String>,
+}
+
+impl Response for ConfigRemoveServerResponse {
+    fn is_success(&self) -> bool {
+        ResponseCode::Ok == self.resultCode
+    }
+
+    fn get_request_id(&self) -> Option<&String> {
+        Option::from(&self.requestId)
+    }
+
+    fn get_message(&self) -> Option<&String> {
+        Option::from(&self.message)
+    }
+
+    fn get_error_code(&self) -> u32 {
+        self.errorCode
+    }
+
+    fn get_type_url(&self) -> &String {
+        &TYPE_CONFIG_REMOVE_SERVER_RESPONSE
+    }
 }
 
-impl From<&str> for ConfigQueryServerResponse {
-    fn from(json_str: &str) -> Self {
-        let de: serde_json::Result<Self> = serde_json::from_str(json_str);
-        de.unwrap()
+impl TryFrom<&str> for ConfigRemoveServerResponse {
+    type Error = crate::api::error::Error;
+
+    fn try_from(json_str: &str) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(json_str)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::remote::response::Response;
+    use crate::config::server_response::{
+        ConfigChangeBatchListenServerResponse, ConfigPublishServerResponse,
+        ConfigQueryServerResponse, ConfigRemoveServerResponse,
+    };
+
+    #[test]
+    fn test_batch_listen_server_response() {
+        let changed = ConfigChangeBatchListenServerResponse::try_from(
+            r#"{"resultCode":200,"errorCode":0,"changedConfigs":[{"dataId":"d1","group":"g1"}]}"#,
+        )
+        .unwrap();
+        assert!(changed.is_success());
+        let changed_configs = changed.get_changed_configs().unwrap();
+        assert_eq!("d1", changed_configs[0].dataId);
+        assert_eq!("", changed_configs[0].tenant);
+
+        assert!(matches!(
+            ConfigChangeBatchListenServerResponse::try_from(r#"{"changedConfigs":"#),
+            Err(crate::api::error::Error::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_server_response() {
+        let ok = ConfigQueryServerResponse::try_from(
+            r#"{"resultCode":200,"errorCode":0,"content":"a=1","md5":"md5-1"}"#,
+        )
+        .unwrap();
+        assert!(ok.is_success());
+        assert_eq!("a=1", ok.get_content());
+
+        let not_found =
+            ConfigQueryServerResponse::try_from(r#"{"resultCode":500,"errorCode":300}"#).unwrap();
+        assert!(not_found.is_not_found());
+
+        assert!(matches!(
+            ConfigQueryServerResponse::try_from("<html>bad gateway</html>"),
+            Err(crate::api::error::Error::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_publish_server_response() {
+        let ok = ConfigPublishServerResponse::try_from(
+            r#"{"requestId":"1","resultCode":200,"errorCode":0}"#,
+        )
+        .unwrap();
+        assert!(ok.is_success());
+
+        let err = ConfigPublishServerResponse::try_from(
+            r#"{"resultCode":500,"errorCode":400,"message":"cas publish fail"}"#,
+        )
+        .unwrap();
+        assert!(!err.is_success());
+        assert_eq!(400, err.get_error_code());
+        assert_eq!(Some(&"cas publish fail".to_string()), err.get_message());
+
+        assert!(matches!(
+            ConfigPublishServerResponse::try_from("<html>bad gateway</html>"),
+            Err(crate::api::error::Error::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_remove_server_response() {
+        let ok =
+            ConfigRemoveServerResponse::try_from(r#"{"resultCode":200,"errorCode":0}"#).unwrap();
+        assert!(ok.is_success());
+
+        let err = ConfigRemoveServerResponse::try_from(
+            r#"{"resultCode":500,"errorCode":500,"message":"error"}"#,
+        )
+        .unwrap();
+        assert!(!err.is_success());
+        assert_eq!(500, err.get_error_code());
+
+        assert!(ConfigRemoveServerResponse::try_from(r#"{"resultCode":200}"#).is_err());
     }
 }
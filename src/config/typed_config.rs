@@ -0,0 +1,238 @@
+use crate::api::config::ConfigResponse;
+use crate::api::error::{Error, Result};
+use crate::config::change_parser;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{DeserializeOwned, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use std::collections::BTreeMap;
+
+/// Deserialize the config content into T, the parser is picked by the content type
+/// (json, yaml, toml, properties), or by the extension of data_id if content type is text.
+pub(crate) fn deserialize_config<T: DeserializeOwned>(config_resp: &ConfigResponse) -> Result<T> {
+    let content = config_resp.get_content().as_str();
+    let content_type = match config_resp.get_content_type().to_lowercase().as_str() {
+        "" | "text" => config_resp
+            .get_data_id()
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_lowercase())
+            .unwrap_or_default(),
+        content_type => content_type.to_string(),
+    };
+    match content_type.as_str() {
+        "json" => serde_json::from_str(content).map_err(|e| Error::ConfigParse(e.to_string())),
+        "yaml" | "yml" => {
+            serde_yaml::from_str(content).map_err(|e| Error::ConfigParse(e.to_string()))
+        }
+        "toml" => toml::from_str(content).map_err(|e| Error::ConfigParse(e.to_string())),
+        "properties" => T::deserialize(PropertiesValue::parse(content)),
+        _ => Err(Error::ConfigParse(format!(
+            "unsupported content type '{}' of {}",
+            config_resp.get_content_type(),
+            config_resp.get_data_id()
+        ))),
+    }
+}
+
+/// The properties as nested values, `a.b=1` is `{a: {b: "1"}}`,
+/// strings are converted when deserialize into number or bool.
+enum PropertiesValue {
+    Str(String),
+    Map(BTreeMap<String, PropertiesValue>),
+}
+
+impl PropertiesValue {
+    fn parse(content: &str) -> Self {
+        let mut root = BTreeMap::new();
+        for (key, value) in change_parser::parse_properties(content) {
+            let mut map = &mut root;
+            let mut parts = key.split('.').peekable();
+            while let Some(part) = parts.next() {
+                if parts.peek().is_none() {
+                    map.insert(part.to_string(), PropertiesValue::Str(value));
+                    break;
+                }
+                let entry = map
+                    .entry(part.to_string())
+                    .or_insert_with(|| PropertiesValue::Map(BTreeMap::new()));
+                if let PropertiesValue::Str(_) = entry {
+                    // both `a=1` and `a.b=2`, the nested one wins.
+                    *entry = PropertiesValue::Map(BTreeMap::new());
+                }
+                map = match entry {
+                    PropertiesValue::Map(m) => m,
+                    PropertiesValue::Str(_) => unreachable!(),
+                };
+            }
+        }
+        PropertiesValue::Map(root)
+    }
+
+    fn parse_str<V: std::str::FromStr>(self) -> Result<V> {
+        match self {
+            PropertiesValue::Str(s) => s
+                .trim()
+                .parse()
+                .map_err(|_| Error::ConfigParse(format!("invalid value '{}'", s))),
+            PropertiesValue::Map(_) => Err(Error::ConfigParse(
+                "expected a value, found nested keys".to_string(),
+            )),
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for PropertiesValue {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! deserialize_from_str {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                visitor.$visit(self.parse_str()?)
+            }
+        )*
+    };
+}
+
+impl<'de> serde::Deserializer<'de> for PropertiesValue {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            PropertiesValue::Str(s) => visitor.visit_string(s),
+            PropertiesValue::Map(m) => visitor.visit_map(MapDeserializer::new(m.into_iter())),
+        }
+    }
+
+    deserialize_from_str! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    /// Comma separated values, e.g. `hosts=a,b,c`.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            PropertiesValue::Str(s) => {
+                let values: Vec<PropertiesValue> = s
+                    .split(',')
+                    .map(|v| PropertiesValue::Str(v.trim().to_string()))
+                    .filter(|v| !matches!(v, PropertiesValue::Str(v) if v.is_empty()))
+                    .collect();
+                visitor.visit_seq(SeqDeserializer::new(values.into_iter()))
+            }
+            map => map.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self {
+            PropertiesValue::Str(s) => visitor.visit_enum(s.into_deserializer()),
+            map => map.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::config::ConfigResponse;
+    use crate::config::typed_config::deserialize_config;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Server {
+        port: u16,
+        enabled: bool,
+        hosts: Vec<String>,
+        name: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct App {
+        server: Server,
+    }
+
+    fn config_resp(data_id: &str, content_type: &str, content: &str) -> ConfigResponse {
+        ConfigResponse::new(
+            data_id.to_string(),
+            "g".to_string(),
+            "".to_string(),
+            content.to_string(),
+            content_type.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_deserialize_config() {
+        let expected = App {
+            server: Server {
+                port: 8080,
+                enabled: true,
+                hosts: vec!["a".to_string(), "b".to_string()],
+                name: None,
+            },
+        };
+
+        let json = r#"{"server":{"port":8080,"enabled":true,"hosts":["a","b"]}}"#;
+        let app: App = deserialize_config(&config_resp("app", "json", json)).unwrap();
+        assert_eq!(expected, app);
+
+        let yaml = "server:\n  port: 8080\n  enabled: true\n  hosts: [a, b]\n";
+        let app: App = deserialize_config(&config_resp("app", "yaml", yaml)).unwrap();
+        assert_eq!(expected, app);
+
+        let toml = "[server]\nport = 8080\nenabled = true\nhosts = [\"a\", \"b\"]\n";
+        let app: App = deserialize_config(&config_resp("app.toml", "text", toml)).unwrap();
+        assert_eq!(expected, app);
+
+        let properties = "server.port=8080\nserver.enabled=true\nserver.hosts=a,b\n";
+        let app: App = deserialize_config(&config_resp("app", "properties", properties)).unwrap();
+        assert_eq!(expected, app);
+    }
+
+    #[test]
+    fn test_deserialize_config_failed() {
+        let r: crate::api::error::Result<App> =
+            deserialize_config(&config_resp("app", "properties", "server.port=x"));
+        assert!(matches!(r, Err(crate::api::error::Error::ConfigParse(_))));
+
+        let r: crate::api::error::Result<App> =
+            deserialize_config(&config_resp("app", "text", "whatever"));
+        assert!(matches!(r, Err(crate::api::error::Error::ConfigParse(_))));
+    }
+}
@@ -1,43 +1,279 @@
 use crate::api::client_config::ClientConfig;
-use crate::api::config::ConfigResponse;
+use crate::api::config::{
+    ConfigChangeEvent, ConfigChangeEventListener, ConfigChangeListener, ConfigFilterData,
+    ConfigResponse, ListenerId,
+};
+use crate::common::remote::conn::Connection;
+use crate::common::remote::response::Response;
+use crate::config::change_parser;
+use crate::config::client_request::{
+    ConfigBatchListenClientRequest, ConfigListenContext, ConfigQueryClientRequest,
+};
+use crate::config::filter::ConfigFilterChain;
+use crate::config::local_config::LocalConfigInfoProcessor;
+use crate::config::server_response::{
+    ConfigChangeBatchListenServerResponse, ConfigQueryServerResponse,
+};
 use crate::config::util;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The id generator of config change listener.
+static LISTENER_ID_SEQUENCE: AtomicU64 = AtomicU64::new(1);
+
+/// The max count of ConfigListenContext in one ConfigBatchListenRequest.
+const LISTEN_BATCH_SIZE: usize = 3000;
+
+/// The timeout of query the newest config when config changed.
+const QUERY_CONFIG_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The kinds of config change listener.
+pub(crate) enum ConfigListener {
+    /// Receive the newest ConfigResponse.
+    Response(Box<ConfigChangeListener>),
+    /// Receive the ConfigChangeEvent with key-level diffs.
+    ChangeEvent(Box<ConfigChangeEventListener>),
+    /// Send the newest ConfigResponse to the watch channel, dropped once all receivers dropped.
+    Watch(tokio::sync::watch::Sender<ConfigResponse>),
+}
 
 #[derive(Clone)]
 pub(crate) struct ConfigWorker {
     client_config: ClientConfig,
     cache_data_map: Arc<Mutex<HashMap<String, CacheData>>>,
+    /// local snapshot and failover of config
+    local_config: LocalConfigInfoProcessor,
+    /// applied on the content from server, failover and snapshot
+    config_filter_chain: ConfigFilterChain,
 }
 
 impl ConfigWorker {
-    pub(crate) fn new(client_config: ClientConfig) -> Self {
-        let client_worker = Self {
+    pub(crate) fn new(client_config: ClientConfig, config_filter_chain: ConfigFilterChain) -> Self {
+        let local_config = LocalConfigInfoProcessor::new(client_config.config_cache_dir.as_str());
+        Self {
             client_config,
             cache_data_map: Arc::new(Mutex::new(HashMap::new())),
+            local_config,
+            config_filter_chain,
+        }
+    }
+
+    /// Get config, failover takes precedence, then query from server,
+    /// fall back to the snapshot when server is unreachable.
+    /// The content type of snapshot is saved with it, failover regards the snapshot's one
+    /// as its content type, or text if absent.
+    pub(crate) async fn get_config(
+        &mut self,
+        conn: &mut Connection,
+        data_id: String,
+        group: String,
+        tenant: String,
+        timeout: Duration,
+    ) -> crate::api::error::Result<ConfigResponse> {
+        let group_key = util::group_key(&data_id, &group, &tenant);
+        let local_config_resp = |content: String| {
+            let content_type = self
+                .local_config
+                .get_content_type_snapshot(&group_key)
+                .unwrap_or_else(|| "text".to_string());
+            ConfigResponse::new(
+                data_id.clone(),
+                group.clone(),
+                tenant.clone(),
+                content,
+                content_type,
+            )
         };
+        if let Some(content) = self.local_config.get_failover(&group_key) {
+            tracing::warn!("get config {} from failover", group_key);
+            let encrypted_data_key = self
+                .local_config
+                .get_encrypted_data_key_failover(&group_key);
+            let content =
+                self.filter_query(&data_id, &group, &tenant, content, encrypted_data_key)?;
+            return Ok(local_config_resp(content));
+        }
+        match Self::query_config(
+            conn,
+            data_id.clone(),
+            group.clone(),
+            tenant.clone(),
+            timeout,
+        )
+        .await
+        {
+            Ok(config_resp) => {
+                self.save_snapshot(&group_key, &config_resp);
+                let content = self.filter_query(
+                    &data_id,
+                    &group,
+                    &tenant,
+                    config_resp.get_content().clone(),
+                    config_resp.get_encrypted_Data_Key().cloned(),
+                )?;
+                Ok(ConfigResponse::new(
+                    data_id.clone(),
+                    group.clone(),
+                    tenant.clone(),
+                    content,
+                    config_resp.get_content_type().clone(),
+                ))
+            }
+            // server returns error result, no need to fall back.
+            Err(err @ crate::api::error::Error::ErrResult(_)) => Err(err),
+            Err(err) => match self.local_config.get_snapshot(&group_key) {
+                Some(content) => {
+                    tracing::warn!(
+                        "get config {} from snapshot, because of {:?}",
+                        group_key,
+                        err
+                    );
+                    let encrypted_data_key = self
+                        .local_config
+                        .get_encrypted_data_key_snapshot(&group_key);
+                    let content =
+                        self.filter_query(&data_id, &group, &tenant, content, encrypted_data_key)?;
+                    Ok(local_config_resp(content))
+                }
+                None => Err(err),
+            },
+        }
+    }
 
-        tokio::spawn(Self::list_ensure_cache_data_newest());
+    /// Save the config from server into snapshot, remove it if config not found.
+    /// The content is saved as it is from server, with its encrypted data key if any,
+    /// and its content type.
+    fn save_snapshot(&self, group_key: &String, config_resp: &ConfigQueryServerResponse) {
+        if config_resp.is_not_found() {
+            self.local_config.remove_snapshot(group_key);
+            self.local_config
+                .save_encrypted_data_key_snapshot(group_key, None);
+            self.local_config
+                .save_content_type_snapshot(group_key, None);
+        } else {
+            self.local_config
+                .save_content_type_snapshot(group_key, Some(config_resp.get_content_type()));
+            self.local_config
+                .save_snapshot(group_key, config_resp.get_content());
+            self.local_config
+                .save_encrypted_data_key_snapshot(group_key, config_resp.get_encrypted_Data_Key());
+        }
+    }
 
-        client_worker
+    /// Apply the config filters on the content, e.g. decrypt it, return the filtered content.
+    fn filter_query(
+        &self,
+        data_id: &String,
+        group: &String,
+        tenant: &String,
+        content: String,
+        encrypted_data_key: Option<String>,
+    ) -> crate::api::error::Result<String> {
+        let mut config = ConfigFilterData {
+            data_id: data_id.clone(),
+            group: group.clone(),
+            namespace: tenant.clone(),
+            content,
+            encrypted_data_key,
+        };
+        self.config_filter_chain.filter_query(&mut config)?;
+        Ok(config.content)
     }
 
     /// List-Watch, list ensure cache-data newest.
-    async fn list_ensure_cache_data_newest() {
+    /// Batch listen all cache-data with their md5, then refresh and notify the changed ones,
+    /// so that changes missed during reconnect or a dropped push are recovered.
+    /// Return false if some batch listen failed.
+    pub(crate) async fn list_ensure_cache_data_newest(&mut self, conn: &mut Connection) -> bool {
+        let mut success = true;
+        for contexts in self.listen_context_batches() {
+            let req = ConfigBatchListenClientRequest::new(true).config_listen_context(contexts);
+            let payload_inner = match conn.send_client_req(req).await {
+                Ok(payload_inner) => payload_inner,
+                Err(err) => {
+                    tracing::warn!("batch listen config failed, {:?}", err);
+                    return false;
+                }
+            };
+            let batch_listen_resp = match ConfigChangeBatchListenServerResponse::try_from(
+                payload_inner.body_str.as_str(),
+            ) {
+                Ok(batch_listen_resp) => batch_listen_resp,
+                Err(err) => {
+                    tracing::warn!("batch listen config failed, {:?}", err);
+                    success = false;
+                    continue;
+                }
+            };
+            if !batch_listen_resp.is_success() {
+                tracing::warn!(
+                    "batch listen config failed, error_code={},message={}",
+                    batch_listen_resp.get_error_code(),
+                    batch_listen_resp.get_message().unwrap_or(&"".to_string())
+                );
+                success = false;
+                continue;
+            }
+            if let Some(changed_configs) = batch_listen_resp.get_changed_configs() {
+                for changed in changed_configs {
+                    tracing::info!(
+                        "list ensure config changed, dataId={},group={},namespace={}",
+                        &changed.dataId,
+                        &changed.group,
+                        &changed.tenant
+                    );
+                    self.notify_config_change(
+                        conn,
+                        changed.dataId.clone(),
+                        changed.group.clone(),
+                        changed.tenant.clone(),
+                    )
+                    .await;
+                }
+            }
+        }
+        success
+    }
+
+    /// All cache-data as ConfigListenContext, at most LISTEN_BATCH_SIZE in a batch.
+    fn listen_context_batches(&self) -> Vec<Vec<ConfigListenContext>> {
+        self.listen_contexts()
+            .chunks(LISTEN_BATCH_SIZE)
+            .map(|contexts| contexts.to_vec())
+            .collect()
+    }
+
+    /// All cache-data as ConfigListenContext with their current md5.
+    fn listen_contexts(&self) -> Vec<ConfigListenContext> {
         loop {
-            // todo query from server
-            tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+            let cache_lock = self.cache_data_map.try_lock();
+            if let Ok(mutex) = cache_lock {
+                return mutex
+                    .values()
+                    .map(|c| {
+                        ConfigListenContext::new(
+                            c.data_id.clone(),
+                            c.group.clone(),
+                            c.tenant.clone(),
+                            c.md5.clone(),
+                        )
+                    })
+                    .collect();
+            }
         }
     }
 
-    /// Add listener.
+    /// Add listener, return the id of listener.
     pub(crate) fn add_listener(
         &mut self,
         data_id: String,
         group: String,
         tenant: String,
-        listener: Box<crate::api::config::ConfigChangeListener>,
-    ) {
+        listener: ConfigListener,
+    ) -> ListenerId {
+        let listener_id = ListenerId(LISTENER_ID_SEQUENCE.fetch_add(1, Ordering::Relaxed));
         let group_key = util::group_key(&data_id, &group, &tenant);
         loop {
             let cache_lock = self.cache_data_map.try_lock();
@@ -50,35 +286,217 @@ impl ConfigWorker {
                 }
                 let _ = mutex
                     .get_mut(group_key.as_str())
-                    .map(|c| c.add_listener(listener));
+                    .map(|c| c.add_listener(listener_id, listener));
                 break;
             }
         }
+        listener_id
     }
 
-    /// notify config change
-    pub(crate) fn notify_config_change(&mut self, data_id: String, group: String, tenant: String) {
+    /// Remove listener, return true if no listener left and the cache-data was removed.
+    pub(crate) fn remove_listener(
+        &mut self,
+        data_id: String,
+        group: String,
+        tenant: String,
+        listener_id: ListenerId,
+    ) -> bool {
         let group_key = util::group_key(&data_id, &group, &tenant);
         loop {
             let cache_lock = self.cache_data_map.try_lock();
             if let Ok(mut mutex) = cache_lock {
-                if !mutex.contains_key(group_key.as_str()) {
-                    break;
+                let no_listener = match mutex.get_mut(group_key.as_str()) {
+                    None => return false,
+                    Some(c) => c.remove_listener(listener_id),
+                };
+                if no_listener {
+                    mutex.remove(group_key.as_str());
+                }
+                return no_listener;
+            }
+        }
+    }
+
+    /// Notify config change, query the newest config from server,
+    /// then notify listeners only if the md5 changed.
+    /// Failover takes precedence as `get_config` does, the server is not queried if exists.
+    pub(crate) async fn notify_config_change(
+        &mut self,
+        conn: &mut Connection,
+        data_id: String,
+        group: String,
+        tenant: String,
+    ) {
+        let group_key = util::group_key(&data_id, &group, &tenant);
+        if !self.contains_cache_data(&group_key) {
+            return;
+        }
+        if self.apply_failover(&data_id, &group, &tenant) {
+            tracing::warn!(
+                "config {} changed, but failover takes precedence",
+                group_key
+            );
+            return;
+        }
+        let config_resp = match Self::query_config(
+            conn,
+            data_id.clone(),
+            group.clone(),
+            tenant.clone(),
+            QUERY_CONFIG_TIMEOUT,
+        )
+        .await
+        {
+            Ok(config_resp) => config_resp,
+            Err(err) => {
+                tracing::warn!("query the newest config {} failed, {:?}", group_key, err);
+                return;
+            }
+        };
+        self.update_cache_data(data_id, group, tenant, config_resp);
+    }
+
+    /// Initialize the new cache-data with the current config, failover or from server,
+    /// otherwise its empty md5 is regarded as changed by the first list-ensure.
+    /// Its listeners are not notified, except the watch channels whose config is stale,
+    /// e.g. changed since `watch` got it.
+    pub(crate) async fn init_cache_data(
+        &mut self,
+        conn: &mut Connection,
+        data_id: String,
+        group: String,
+        tenant: String,
+    ) {
+        let group_key = util::group_key(&data_id, &group, &tenant);
+        if !self.set_initializing(&group_key, true) {
+            return;
+        }
+        self.notify_config_change(conn, data_id, group, tenant)
+            .await;
+        self.set_initializing(&group_key, false);
+    }
+
+    /// Mark the cache-data initializing, only if it is not synced with server yet,
+    /// i.e. its md5 is empty, or unmark it. Return false if not marked or unmarked.
+    fn set_initializing(&self, group_key: &String, initializing: bool) -> bool {
+        loop {
+            let cache_lock = self.cache_data_map.try_lock();
+            if let Ok(mut mutex) = cache_lock {
+                return match mutex.get_mut(group_key.as_str()) {
+                    Some(c) if c.md5.is_empty() || !initializing => {
+                        c.initializing = initializing;
+                        true
+                    }
+                    _ => false,
+                };
+            }
+        }
+    }
+
+    /// Update the cache-data with the failover config if exists, notify its listeners
+    /// if the content changed. The md5 is cleared, so that the config from server is
+    /// restored by list-ensure once the failover is removed. Return true if exists.
+    fn apply_failover(&mut self, data_id: &String, group: &String, tenant: &String) -> bool {
+        let group_key = util::group_key(data_id, group, tenant);
+        let content = match self.local_config.get_failover(&group_key) {
+            Some(content) => content,
+            None => return false,
+        };
+        let encrypted_data_key = self
+            .local_config
+            .get_encrypted_data_key_failover(&group_key);
+        let content = match self.filter_query(data_id, group, tenant, content, encrypted_data_key) {
+            Ok(content) => content,
+            Err(err) => {
+                tracing::warn!("filter the failover config {} failed, {:?}", group_key, err);
+                return true;
+            }
+        };
+        loop {
+            let cache_lock = self.cache_data_map.try_lock();
+            if let Ok(mut mutex) = cache_lock {
+                if let Some(c) = mutex.get_mut(group_key.as_str()) {
+                    c.md5 = String::new();
+                    if c.content != content {
+                        let old_content = std::mem::replace(&mut c.content, content);
+                        c.notify_listener(old_content);
+                    }
+                }
+                return true;
+            }
+        }
+    }
+
+    /// Update the cache-data with the newest config from server,
+    /// notify its listeners only if the md5 changed.
+    fn update_cache_data(
+        &mut self,
+        data_id: String,
+        group: String,
+        tenant: String,
+        config_resp: ConfigQueryServerResponse,
+    ) {
+        let group_key = util::group_key(&data_id, &group, &tenant);
+        self.save_snapshot(&group_key, &config_resp);
+        let content = match self.filter_query(
+            &data_id,
+            &group,
+            &tenant,
+            config_resp.get_content().clone(),
+            config_resp.get_encrypted_Data_Key().cloned(),
+        ) {
+            Ok(content) => content,
+            Err(err) => {
+                tracing::warn!("filter the newest config {} failed, {:?}", group_key, err);
+                return;
+            }
+        };
+        let config_resp = config_resp.content(content);
+        loop {
+            let cache_lock = self.cache_data_map.try_lock();
+            if let Ok(mut mutex) = cache_lock {
+                if let Some(c) = mutex.get_mut(group_key.as_str()) {
+                    let old_content = c.content.clone();
+                    if c.update_config(&config_resp) {
+                        c.notify_listener(old_content);
+                    }
                 }
-                let _ = mutex.get_mut(group_key.as_str()).map(|c| {
-                    // todo get the newest config to notify
-                    c.notify_listener(ConfigResponse::new(
-                        c.data_id.clone(),
-                        c.group.clone(),
-                        c.tenant.clone(),
-                        c.content.clone(),
-                        c.content_type.clone(),
-                    ))
-                });
                 break;
             }
         }
     }
+
+    /// Query config from server within timeout, config not found is regarded as empty content.
+    pub(crate) async fn query_config(
+        conn: &mut Connection,
+        data_id: String,
+        group: String,
+        tenant: String,
+        timeout: Duration,
+    ) -> crate::api::error::Result<ConfigQueryServerResponse> {
+        let req = ConfigQueryClientRequest::new(data_id, group, tenant);
+        let payload_inner = conn.send_client_req_timeout(req, timeout).await?;
+        // a malformed body is Error::Serialization, so that it falls back to the snapshot.
+        let config_resp = ConfigQueryServerResponse::try_from(payload_inner.body_str.as_str())?;
+        if config_resp.is_success() || config_resp.is_not_found() {
+            Ok(config_resp)
+        } else {
+            Err(crate::api::error::Error::ErrResult(format!(
+                "query config failed, error_code={},message={}",
+                config_resp.get_error_code(),
+                config_resp.get_message().unwrap_or(&"".to_string())
+            )))
+        }
+    }
+
+    fn contains_cache_data(&self, group_key: &String) -> bool {
+        loop {
+            let cache_lock = self.cache_data_map.try_lock();
+            if let Ok(mutex) = cache_lock {
+                return mutex.contains_key(group_key.as_str());
+            }
+        }
+    }
 }
 
 /// Cache Data for Config
@@ -101,7 +519,7 @@ struct CacheData {
     need_sync_server: bool,
 
     /// who listen of config change.
-    listeners: Arc<Mutex<Vec<Box<crate::api::config::ConfigChangeListener>>>>,
+    listeners: Arc<Mutex<Vec<(ListenerId, ConfigListener)>>>,
 }
 
 impl CacheData {
@@ -116,26 +534,450 @@ impl CacheData {
     }
 
     /// Add listener.
-    fn add_listener(&mut self, listener: Box<crate::api::config::ConfigChangeListener>) {
+    fn add_listener(&mut self, listener_id: ListenerId, listener: ConfigListener) {
         loop {
             let listen_lock = self.listeners.try_lock();
             if let Ok(mut mutex) = listen_lock {
-                mutex.push(listener);
+                mutex.push((listener_id, listener));
                 break;
             }
         }
     }
 
-    /// Notify listener.
-    fn notify_listener(&mut self, config_response: ConfigResponse) {
+    /// Remove listener, return true if no listener left.
+    fn remove_listener(&mut self, listener_id: ListenerId) -> bool {
         loop {
             let listen_lock = self.listeners.try_lock();
             if let Ok(mut mutex) = listen_lock {
-                for listen in mutex.iter_mut() {
-                    (listen)(config_response.clone());
+                mutex.retain(|(id, _)| *id != listener_id);
+                return mutex.is_empty();
+            }
+        }
+    }
+
+    /// Update with the config from server, return true if md5 changed.
+    fn update_config(&mut self, config_resp: &ConfigQueryServerResponse) -> bool {
+        if self.md5.eq(config_resp.get_md5()) {
+            return false;
+        }
+        self.content = config_resp.get_content().clone();
+        self.md5 = config_resp.get_md5().clone();
+        if !config_resp.get_content_type().is_empty() {
+            self.content_type = config_resp.get_content_type().clone();
+        }
+        self.encrypted_data_key = config_resp.get_encrypted_Data_Key().cloned();
+        self.last_modified = config_resp.get_last_modified();
+        true
+    }
+
+    /// Notify listener, the old_content is used to build ConfigChangeEvent.
+    fn notify_listener(&mut self, old_content: String) {
+        let config_response = ConfigResponse::new(
+            self.data_id.clone(),
+            self.group.clone(),
+            self.tenant.clone(),
+            self.content.clone(),
+            self.content_type.clone(),
+        );
+        // build once when some listener needs it.
+        let mut change_event: Option<ConfigChangeEvent> = None;
+        loop {
+            let listen_lock = self.listeners.try_lock();
+            if let Ok(mut mutex) = listen_lock {
+                for (_, listen) in mutex.iter_mut() {
+                    match listen {
+                        ConfigListener::Response(listen) if !self.initializing => {
+                            (listen)(config_response.clone())
+                        }
+                        ConfigListener::ChangeEvent(listen) if !self.initializing => {
+                            let event = change_event.get_or_insert_with(|| {
+                                ConfigChangeEvent::new(
+                                    self.data_id.clone(),
+                                    self.group.clone(),
+                                    self.tenant.clone(),
+                                    self.content_type.clone(),
+                                    old_content.clone(),
+                                    self.content.clone(),
+                                    change_parser::parse_change_items(
+                                        self.content_type.as_str(),
+                                        old_content.as_str(),
+                                        self.content.as_str(),
+                                    ),
+                                )
+                            });
+                            (listen)(event.clone())
+                        }
+                        ConfigListener::Watch(sender) => {
+                            // only if stale, the receivers are not woken up for the same one.
+                            sender.send_if_modified(|current| {
+                                let stale = current.get_content() != config_response.get_content()
+                                    || current.get_content_type()
+                                        != config_response.get_content_type();
+                                if stale {
+                                    *current = config_response.clone();
+                                }
+                                stale
+                            });
+                        }
+                        // not notified while initializing.
+                        _ => {}
+                    }
                 }
+                mutex.retain(|(_, listen)| {
+                    !matches!(listen, ConfigListener::Watch(sender) if sender.is_closed())
+                });
                 break;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::client_config::ClientConfig;
+    use crate::config::filter::ConfigFilterChain;
+    use crate::config::server_response::ConfigQueryServerResponse;
+    use crate::config::worker::{CacheData, ConfigListener, ConfigWorker, LISTEN_BATCH_SIZE};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// A unique config cache dir of each test, removed when dropped.
+    struct TempCacheDir(std::path::PathBuf);
+
+    impl TempCacheDir {
+        fn new() -> Self {
+            static SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+            TempCacheDir(std::env::temp_dir().join(format!(
+                "nacos-config-worker-{}-{}",
+                std::process::id(),
+                SEQUENCE.fetch_add(1, Ordering::Relaxed)
+            )))
+        }
+    }
+
+    impl Drop for TempCacheDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn test_client_worker(cache_dir: &TempCacheDir) -> ConfigWorker {
+        ConfigWorker::new(
+            ClientConfig::new().config_cache_dir(cache_dir.0.to_str().unwrap()),
+            ConfigFilterChain::default(),
+        )
+    }
+
+    #[test]
+    fn test_add_remove_listener() {
+        let cache_dir = TempCacheDir::new();
+        let mut client_worker = test_client_worker(&cache_dir);
+        let (d, g, t) = ("d".to_string(), "g".to_string(), "t".to_string());
+
+        let id1 = client_worker.add_listener(
+            d.clone(),
+            g.clone(),
+            t.clone(),
+            ConfigListener::Response(Box::new(|_| {})),
+        );
+        let id2 = client_worker.add_listener(
+            d.clone(),
+            g.clone(),
+            t.clone(),
+            ConfigListener::ChangeEvent(Box::new(|_| {})),
+        );
+        assert_ne!(id1, id2);
+
+        assert!(!client_worker.remove_listener(d.clone(), g.clone(), t.clone(), id1));
+        assert!(client_worker.remove_listener(d.clone(), g.clone(), t.clone(), id2));
+        // cache-data was removed, remove again is nothing.
+        assert!(!client_worker.remove_listener(d, g, t, id2));
+    }
+
+    #[test]
+    fn test_cache_data_update_config() {
+        let mut cache_data = CacheData::new("d".to_string(), "g".to_string(), "t".to_string());
+        let config_resp = ConfigQueryServerResponse::try_from(
+            r#"{"resultCode":200,"errorCode":0,"content":"k=v","md5":"m1","contentType":"properties","lastModified":1}"#,
+        ).unwrap();
+        assert!(cache_data.update_config(&config_resp));
+        assert_eq!("k=v", cache_data.content);
+        assert_eq!("properties", cache_data.content_type);
+        // md5 not changed, no need to notify.
+        assert!(!cache_data.update_config(&config_resp));
+
+        // config not found, content absent.
+        let config_resp =
+            ConfigQueryServerResponse::try_from(r#"{"resultCode":500,"errorCode":300}"#).unwrap();
+        assert!(config_resp.is_not_found());
+        assert!(cache_data.update_config(&config_resp));
+        assert_eq!("", cache_data.content);
+    }
+
+    #[test]
+    fn test_cache_data_notify_listener() {
+        let mut cache_data = CacheData::new("d".to_string(), "g".to_string(), "t".to_string());
+        let (sender, receiver) = tokio::sync::watch::channel(String::new());
+        cache_data.add_listener(
+            crate::api::config::ListenerId(1),
+            ConfigListener::Response(Box::new(move |config_resp| {
+                let _ = sender.send(config_resp.get_content().clone());
+            })),
+        );
+        let config_resp = ConfigQueryServerResponse::try_from(
+            r#"{"resultCode":200,"errorCode":0,"content":"k=v","md5":"m1","contentType":"properties"}"#,
+        ).unwrap();
+        let old_content = cache_data.content.clone();
+        assert!(cache_data.update_config(&config_resp));
+        cache_data.notify_listener(old_content);
+        assert_eq!("k=v", receiver.borrow().as_str());
+    }
+
+    #[test]
+    fn test_listen_context_batches() {
+        let cache_dir = TempCacheDir::new();
+        let mut client_worker = test_client_worker(&cache_dir);
+        assert!(client_worker.listen_context_batches().is_empty());
+        for i in 0..LISTEN_BATCH_SIZE + 1 {
+            client_worker.add_listener(
+                format!("d{}", i),
+                "g".to_string(),
+                "t".to_string(),
+                ConfigListener::Response(Box::new(|_| {})),
+            );
+        }
+        let batch_sizes: Vec<usize> = client_worker
+            .listen_context_batches()
+            .iter()
+            .map(Vec::len)
+            .collect();
+        assert_eq!(vec![LISTEN_BATCH_SIZE, 1], batch_sizes);
+    }
+
+    #[test]
+    fn test_update_cache_data_notify_changed_only() {
+        let cache_dir = TempCacheDir::new();
+        let mut client_worker = test_client_worker(&cache_dir);
+        let (g, t) = ("g".to_string(), "t".to_string());
+        let notified: Vec<Arc<AtomicUsize>> = ["d1", "d2"]
+            .iter()
+            .map(|d| {
+                let count = Arc::new(AtomicUsize::new(0));
+                let listener_count = count.clone();
+                client_worker.add_listener(
+                    d.to_string(),
+                    g.clone(),
+                    t.clone(),
+                    ConfigListener::Response(Box::new(move |_| {
+                        listener_count.fetch_add(1, Ordering::SeqCst);
+                    })),
+                );
+                count
+            })
+            .collect();
+        let config_resp = |md5: &str| {
+            ConfigQueryServerResponse::try_from(
+                format!(
+                    r#"{{"resultCode":200,"errorCode":0,"content":"k={}","md5":"{}"}}"#,
+                    md5, md5
+                )
+                .as_str(),
+            )
+            .unwrap()
+        };
+
+        client_worker.update_cache_data("d1".to_string(), g.clone(), t.clone(), config_resp("m1"));
+        assert_eq!(1, notified[0].load(Ordering::SeqCst));
+        assert_eq!(0, notified[1].load(Ordering::SeqCst));
+
+        // md5 not changed, e.g. listed again after reconnect.
+        client_worker.update_cache_data("d1".to_string(), g.clone(), t.clone(), config_resp("m1"));
+        assert_eq!(1, notified[0].load(Ordering::SeqCst));
+
+        client_worker.update_cache_data("d1".to_string(), g.clone(), t.clone(), config_resp("m2"));
+        assert_eq!(2, notified[0].load(Ordering::SeqCst));
+        assert_eq!(0, notified[1].load(Ordering::SeqCst));
+
+        // the next batch listen carries the newest md5.
+        let group_key = crate::config::util::group_key(&"d1".to_string(), &g, &t);
+        let cache_data_map = client_worker.cache_data_map.lock().unwrap();
+        assert_eq!("m2", cache_data_map[&group_key].md5);
+        assert_eq!("k=m2", cache_data_map[&group_key].content);
+    }
+
+    #[test]
+    fn test_apply_failover() {
+        let cache_dir = TempCacheDir::new();
+        let mut client_worker = test_client_worker(&cache_dir);
+        let (d, g, t) = ("d".to_string(), "g".to_string(), "t".to_string());
+        let (sender, receiver) = tokio::sync::watch::channel(String::new());
+        client_worker.add_listener(
+            d.clone(),
+            g.clone(),
+            t.clone(),
+            ConfigListener::Response(Box::new(move |config_resp| {
+                let _ = sender.send(config_resp.get_content().clone());
+            })),
+        );
+        client_worker.update_cache_data(
+            d.clone(),
+            g.clone(),
+            t.clone(),
+            ConfigQueryServerResponse::try_from(
+                r#"{"resultCode":200,"errorCode":0,"content":"k=server","md5":"m1"}"#,
+            )
+            .unwrap(),
+        );
+        assert_eq!("k=server", receiver.borrow().as_str());
+
+        assert!(!client_worker.apply_failover(&d, &g, &t));
+        let group_key = crate::config::util::group_key(&d, &g, &t);
+        let failover_dir = cache_dir.0.join("failover");
+        std::fs::create_dir_all(&failover_dir).unwrap();
+        std::fs::write(failover_dir.join(&group_key), "k=failover").unwrap();
+        assert!(client_worker.apply_failover(&d, &g, &t));
+        assert_eq!("k=failover", receiver.borrow().as_str());
+        // md5 cleared, the server config is restored by list-ensure after failover removed.
+        assert_eq!(
+            "",
+            client_worker.cache_data_map.lock().unwrap()[&group_key].md5
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_config_from_snapshot_keeps_content_type() {
+        let cache_dir = TempCacheDir::new();
+        let mut client_worker = test_client_worker(&cache_dir);
+        let (d, g, t) = ("app".to_string(), "g".to_string(), "t".to_string());
+        let group_key = crate::config::util::group_key(&d, &g, &t);
+        client_worker.save_snapshot(
+            &group_key,
+            &ConfigQueryServerResponse::try_from(
+                r#"{"resultCode":200,"errorCode":0,"content":"{\"k\":\"v\"}","md5":"m1","contentType":"json"}"#,
+            ).unwrap(),
+        );
+
+        // server is unreachable, fall back to the snapshot.
+        let mut conn = crate::common::remote::conn::Connection::new(
+            ClientConfig::new().server_addr("127.0.0.1:1"),
+        );
+        let config_resp = client_worker
+            .get_config(&mut conn, d, g, t, std::time::Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert_eq!("json", config_resp.get_content_type());
+        let config: std::collections::HashMap<String, String> =
+            crate::config::typed_config::deserialize_config(&config_resp).unwrap();
+        assert_eq!("v", config["k"]);
+    }
+
+    #[test]
+    fn test_init_cache_data_not_notify() {
+        let cache_dir = TempCacheDir::new();
+        let mut client_worker = test_client_worker(&cache_dir);
+        let (d, g, t) = ("d".to_string(), "g".to_string(), "t".to_string());
+        let group_key = crate::config::util::group_key(&d, &g, &t);
+        let notified = Arc::new(AtomicUsize::new(0));
+        let counter = notified.clone();
+        client_worker.add_listener(
+            d.clone(),
+            g.clone(),
+            t.clone(),
+            ConfigListener::Response(Box::new(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })),
+        );
+        // watch got the config before listening.
+        let (sender, receiver) =
+            tokio::sync::watch::channel(crate::api::config::ConfigResponse::new(
+                d.clone(),
+                g.clone(),
+                t.clone(),
+                "k=v1".to_string(),
+                "text".to_string(),
+            ));
+        client_worker.add_listener(
+            d.clone(),
+            g.clone(),
+            t.clone(),
+            ConfigListener::Watch(sender),
+        );
+
+        let config_resp = |md5: &str| {
+            ConfigQueryServerResponse::try_from(
+                format!(
+                    r#"{{"resultCode":200,"errorCode":0,"content":"k={}","md5":"{}"}}"#,
+                    md5, md5
+                )
+                .as_str(),
+            )
+            .unwrap()
+        };
+        // initialized as `init_cache_data` does, with the config the listeners have got.
+        assert!(client_worker.set_initializing(&group_key, true));
+        client_worker.update_cache_data(d.clone(), g.clone(), t.clone(), config_resp("v1"));
+        assert!(client_worker.set_initializing(&group_key, false));
+        assert_eq!(0, notified.load(Ordering::SeqCst));
+        assert!(!receiver.has_changed().unwrap());
+        // synced with server, not initialized again.
+        assert!(!client_worker.set_initializing(&group_key, true));
+
+        // md5 unchanged by list-ensure or redo, not notified.
+        client_worker.update_cache_data(d.clone(), g.clone(), t.clone(), config_resp("v1"));
+        assert_eq!(0, notified.load(Ordering::SeqCst));
+        assert!(!receiver.has_changed().unwrap());
+
+        client_worker.update_cache_data(d.clone(), g.clone(), t.clone(), config_resp("v2"));
+        assert_eq!(1, notified.load(Ordering::SeqCst));
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!("k=v2", receiver.borrow().get_content());
+    }
+
+    #[test]
+    fn test_watch_listener() {
+        let cache_dir = TempCacheDir::new();
+        let mut client_worker = test_client_worker(&cache_dir);
+        let (d, g, t) = ("watch".to_string(), "g".to_string(), "t".to_string());
+        let initial = crate::api::config::ConfigResponse::new(
+            d.clone(),
+            g.clone(),
+            t.clone(),
+            "k=v0".to_string(),
+            "properties".to_string(),
+        );
+        let (sender, mut receiver) = tokio::sync::watch::channel(initial);
+        client_worker.add_listener(
+            d.clone(),
+            g.clone(),
+            t.clone(),
+            ConfigListener::Watch(sender),
+        );
+        assert_eq!("k=v0", receiver.borrow().get_content());
+        assert!(!receiver.has_changed().unwrap());
+
+        let config_resp = |md5: &str| {
+            ConfigQueryServerResponse::try_from(
+                format!(
+                    r#"{{"resultCode":200,"errorCode":0,"content":"k={}","md5":"{}"}}"#,
+                    md5, md5
+                )
+                .as_str(),
+            )
+            .unwrap()
+        };
+        client_worker.update_cache_data(d.clone(), g.clone(), t.clone(), config_resp("v1"));
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!("k=v1", receiver.borrow_and_update().get_content());
+
+        // the watch listener is dropped on the next change once the receiver dropped.
+        drop(receiver);
+        client_worker.update_cache_data(d.clone(), g.clone(), t.clone(), config_resp("v2"));
+        let group_key = crate::config::util::group_key(&d, &g, &t);
+        let cache_data_map = client_worker.cache_data_map.lock().unwrap();
+        assert!(cache_data_map[&group_key]
+            .listeners
+            .lock()
+            .unwrap()
+            .is_empty());
+    }
+}
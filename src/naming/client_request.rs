@@ -0,0 +1,201 @@
+#![allow(non_snake_case)]
+use crate::api::naming::ServiceInstance;
+use crate::common::remote::request::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// registerInstance
+pub(crate) const REGISTER_INSTANCE: &str = "registerInstance";
+/// deregisterInstance
+pub(crate) const DE_REGISTER_INSTANCE: &str = "deregisterInstance";
+
+const NAMING_MODULE: &str = "naming";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct InstanceClientRequest {
+    requestId: String,
+    /// count be empty.
+    headers: HashMap<String, String>,
+    /// namespace
+    namespace: String,
+    /// service name
+    serviceName: String,
+    /// group name
+    groupName: String,
+    /// naming
+    module: String,
+    /// registerInstance or deregisterInstance
+    r#type: String,
+    /// the instance
+    instance: ServiceInstance,
+}
+
+impl Request for InstanceClientRequest {
+    fn get_request_id(&self) -> &String {
+        &self.requestId
+    }
+    fn get_headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
+    fn get_type_url(&self) -> &String {
+        &TYPE_INSTANCE_CLIENT_REQUEST
+    }
+    fn get_resource(&self) -> RequestResource {
+        RequestResource::Naming {
+            namespace: self.namespace.clone(),
+            group_name: self.groupName.clone(),
+            service_name: self.serviceName.clone(),
+        }
+    }
+}
+
+impl InstanceClientRequest {
+    pub fn new(
+        r#type: &str,
+        namespace: String,
+        service_name: String,
+        group_name: String,
+        instance: ServiceInstance,
+    ) -> Self {
+        InstanceClientRequest {
+            requestId: generate_request_id(),
+            headers: HashMap::new(),
+            namespace,
+            serviceName: service_name,
+            groupName: group_name,
+            module: NAMING_MODULE.to_string(),
+            r#type: r#type.to_string(),
+            instance,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct ServiceQueryClientRequest {
+    requestId: String,
+    /// count be empty.
+    headers: HashMap<String, String>,
+    /// namespace
+    namespace: String,
+    /// service name
+    serviceName: String,
+    /// group name
+    groupName: String,
+    /// naming
+    module: String,
+    /// clusters joined by comma, empty means all clusters.
+    cluster: String,
+    /// only healthy instances returned if true
+    healthyOnly: bool,
+    /// udp port for push, 0 means no push.
+    udpPort: i32,
+}
+
+impl Request for ServiceQueryClientRequest {
+    fn get_request_id(&self) -> &String {
+        &self.requestId
+    }
+    fn get_headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
+    fn get_type_url(&self) -> &String {
+        &TYPE_SERVICE_QUERY_CLIENT_REQUEST
+    }
+    fn get_resource(&self) -> RequestResource {
+        RequestResource::Naming {
+            namespace: self.namespace.clone(),
+            group_name: self.groupName.clone(),
+            service_name: self.serviceName.clone(),
+        }
+    }
+}
+
+impl ServiceQueryClientRequest {
+    pub fn new(
+        namespace: String,
+        service_name: String,
+        group_name: String,
+        cluster: String,
+        healthy_only: bool,
+    ) -> Self {
+        ServiceQueryClientRequest {
+            requestId: generate_request_id(),
+            headers: HashMap::new(),
+            namespace,
+            serviceName: service_name,
+            groupName: group_name,
+            module: NAMING_MODULE.to_string(),
+            cluster,
+            healthyOnly: healthy_only,
+            udpPort: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct SubscribeServiceClientRequest {
+    requestId: String,
+    /// count be empty.
+    headers: HashMap<String, String>,
+    /// namespace
+    namespace: String,
+    /// service name
+    serviceName: String,
+    /// group name
+    groupName: String,
+    /// naming
+    module: String,
+    /// subscribe if true, otherwise unsubscribe.
+    subscribe: bool,
+    /// clusters joined by comma, empty means all clusters.
+    clusters: String,
+}
+
+impl Request for SubscribeServiceClientRequest {
+    fn get_request_id(&self) -> &String {
+        &self.requestId
+    }
+    fn get_headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
+    fn get_type_url(&self) -> &String {
+        &TYPE_SUBSCRIBE_SERVICE_CLIENT_REQUEST
+    }
+    fn get_resource(&self) -> RequestResource {
+        RequestResource::Naming {
+            namespace: self.namespace.clone(),
+            group_name: self.groupName.clone(),
+            service_name: self.serviceName.clone(),
+        }
+    }
+}
+
+impl SubscribeServiceClientRequest {
+    pub fn new(
+        namespace: String,
+        service_name: String,
+        group_name: String,
+        clusters: String,
+        subscribe: bool,
+    ) -> Self {
+        SubscribeServiceClientRequest {
+            requestId: generate_request_id(),
+            headers: HashMap::new(),
+            namespace,
+            serviceName: service_name,
+            groupName: group_name,
+            module: NAMING_MODULE.to_string(),
+            subscribe,
+            clusters,
+        }
+    }
+}
@@ -1 +1,568 @@
+mod client_request;
+mod client_response;
+mod redo;
+mod server_request;
+mod server_response;
+mod worker;
 
+use crate::api::client_config::ClientConfig;
+use crate::api::error::{Error, Result};
+use crate::api::naming::{
+    grouped_service_name, InstancesChangeEventListener, InstancesChangeListener, NamingService,
+    Selector, ServiceInfo, ServiceInstance, SubscriptionId, WeightedRandomSelector,
+};
+use crate::common::remote::conn::Connection;
+use crate::common::remote::remote_client::{start_remote_client, ServerRequestHandler};
+use crate::common::remote::request::*;
+use crate::common::remote::response::Response;
+use crate::common::util::payload_helper::PayloadInner;
+use crate::naming::client_request::*;
+use crate::naming::client_response::*;
+use crate::naming::redo::NamingRedoService;
+use crate::naming::server_request::*;
+use crate::naming::server_response::*;
+use crate::naming::worker::{InstancesListener, NamingWorker};
+
+pub(crate) struct NacosNamingService {
+    client_config: ClientConfig,
+    connection: Connection,
+    /// naming client worker
+    naming_worker: NamingWorker,
+    /// redo after reconnect
+    redo_service: NamingRedoService,
+}
+
+impl NacosNamingService {
+    pub fn new(client_config: ClientConfig) -> Self {
+        let connection = Connection::new(client_config.clone());
+        Self {
+            client_config,
+            connection,
+            naming_worker: NamingWorker::new(),
+            redo_service: NamingRedoService::new(),
+        }
+    }
+
+    /// start Once
+    pub(crate) async fn start(&mut self) {
+        // not block when the whole cluster is down, the bi-stream loop keeps reconnecting.
+        if !self.connection.connect_once().await {
+            tracing::warn!("no server available now, keep connecting in background");
+        }
+
+        start_remote_client(
+            "naming-remote-client",
+            self.connection.clone(),
+            NamingRemoteHandler {
+                naming_worker: self.naming_worker.clone(),
+                redo_service: self.redo_service.clone(),
+                namespace: self.client_config.namespace.clone(),
+            },
+        );
+
+        // sleep 6ms, Make sure the link is established.
+        tokio::time::sleep(std::time::Duration::from_millis(6)).await
+    }
+
+    /// Register the instances and subscribe the services again, which are not redone
+    /// on the current connection.
+    async fn redo(
+        redo_service: &NamingRedoService,
+        naming_worker: &mut NamingWorker,
+        conn: &mut Connection,
+        namespace: &str,
+    ) {
+        for redo in redo_service.undone_instances() {
+            match Self::request_instance(
+                conn,
+                namespace.to_string(),
+                REGISTER_INSTANCE,
+                redo.service_name.clone(),
+                redo.group_name.clone(),
+                redo.instance.clone(),
+            )
+            .await
+            {
+                Ok(_) => redo_service.instance_redone(&redo),
+                Err(err) => tracing::warn!(
+                    "redo register instance {} of {} failed, {:?}",
+                    redo.instance.ip_and_port(),
+                    redo.service_name,
+                    err
+                ),
+            }
+        }
+        for redo in redo_service.undone_subscribers() {
+            match Self::subscribe_service(
+                conn,
+                namespace.to_string(),
+                redo.service_name.clone(),
+                redo.group_name.clone(),
+                true,
+            )
+            .await
+            {
+                Ok(service_info) => {
+                    // pushes may be missed during reconnect, refresh with the newest one.
+                    let grouped_name = grouped_service_name(&redo.service_name, &redo.group_name);
+                    naming_worker.update_service_info(&grouped_name, service_info);
+                    redo_service.subscriber_redone(&redo);
+                }
+                Err(err) => tracing::warn!(
+                    "redo subscribe service {} failed, {:?}",
+                    redo.service_name,
+                    err
+                ),
+            }
+        }
+    }
+
+    /// Send InstanceRequest, registerInstance or deregisterInstance.
+    async fn request_instance(
+        conn: &mut Connection,
+        namespace: String,
+        r#type: &str,
+        service_name: String,
+        group_name: String,
+        service_instance: ServiceInstance,
+    ) -> Result<()> {
+        let req = InstanceClientRequest::new(
+            r#type,
+            namespace,
+            service_name,
+            group_name,
+            service_instance,
+        );
+        let payload_inner = conn.send_client_req(req).await?;
+        let instance_resp = InstanceServerResponse::try_from(payload_inner.body_str.as_str())?;
+        if !instance_resp.is_success() {
+            return Err(Error::ErrResult(format!(
+                "{} failed, error_code={},message={}",
+                r#type,
+                instance_resp.get_error_code(),
+                instance_resp.get_message().unwrap_or(&"".to_string())
+            )));
+        }
+        Ok(())
+    }
+
+    /// Send ServiceQueryRequest, query the service info with instances in the clusters.
+    async fn query_service_info(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: &[String],
+    ) -> Result<ServiceInfo> {
+        let namespace = self.client_config.namespace.clone();
+        let group_name =
+            group_name.unwrap_or_else(|| crate::api::constants::DEFAULT_GROUP.to_string());
+        let req = ServiceQueryClientRequest::new(
+            namespace,
+            service_name,
+            group_name,
+            clusters.join(","),
+            false,
+        );
+        let payload_inner = self.connection.send_client_req(req).await?;
+        let query_resp = ServiceQueryServerResponse::try_from(payload_inner.body_str.as_str())?;
+        if !query_resp.is_success() {
+            return Err(Error::ErrResult(format!(
+                "query service failed, error_code={},message={}",
+                query_resp.get_error_code(),
+                query_resp.get_message().unwrap_or(&"".to_string())
+            )));
+        }
+        Ok(query_resp.service_info())
+    }
+
+    /// Add the listener, then subscribe the service and notify it.
+    async fn do_subscribe(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+        listener: InstancesListener,
+    ) -> Result<SubscriptionId> {
+        let group_name =
+            group_name.unwrap_or_else(|| crate::api::constants::DEFAULT_GROUP.to_string());
+        let grouped_name = grouped_service_name(&service_name, &group_name);
+        let subscription_id =
+            self.naming_worker
+                .add_listener(grouped_name.clone(), clusters, listener);
+        let service_info = match Self::subscribe_service(
+            &mut self.connection,
+            self.client_config.namespace.clone(),
+            service_name.clone(),
+            group_name.clone(),
+            true,
+        )
+        .await
+        {
+            Ok(service_info) => {
+                self.redo_service
+                    .service_subscribed(service_name, group_name);
+                service_info
+            }
+            Err(err) => {
+                self.naming_worker
+                    .remove_listener(&grouped_name, subscription_id);
+                return Err(err);
+            }
+        };
+        // all listeners notified if changed, otherwise notify the new one only.
+        if !self
+            .naming_worker
+            .update_service_info(&grouped_name, service_info)
+        {
+            self.naming_worker
+                .notify_listener(&grouped_name, subscription_id);
+        }
+        Ok(subscription_id)
+    }
+
+    /// Get the service info, the subscribed service is cached and kept newest by server push,
+    /// otherwise query from server.
+    async fn get_service_info(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: &[String],
+    ) -> Result<ServiceInfo> {
+        let group_name =
+            group_name.unwrap_or_else(|| crate::api::constants::DEFAULT_GROUP.to_string());
+        let grouped_name = grouped_service_name(&service_name, &group_name);
+        match self.naming_worker.get_service_info(&grouped_name) {
+            Some(service_info) => Ok(service_info),
+            None => {
+                self.query_service_info(service_name, Some(group_name), clusters)
+                    .await
+            }
+        }
+    }
+
+    /// Send SubscribeServiceRequest, subscribe or unsubscribe the service.
+    async fn subscribe_service(
+        conn: &mut Connection,
+        namespace: String,
+        service_name: String,
+        group_name: String,
+        subscribe: bool,
+    ) -> Result<ServiceInfo> {
+        // subscribe all clusters, the listeners filter by their clusters.
+        let req = SubscribeServiceClientRequest::new(
+            namespace,
+            service_name,
+            group_name,
+            String::from(""),
+            subscribe,
+        );
+        let payload_inner = conn.send_client_req(req).await?;
+        let subscribe_resp =
+            SubscribeServiceServerResponse::try_from(payload_inner.body_str.as_str())?;
+        if !subscribe_resp.is_success() {
+            return Err(Error::ErrResult(format!(
+                "subscribe service failed, error_code={},message={}",
+                subscribe_resp.get_error_code(),
+                subscribe_resp.get_message().unwrap_or(&"".to_string())
+            )));
+        }
+        Ok(subscribe_resp.service_info())
+    }
+}
+
+/// Enabled instances with weight, only the healthy ones if healthy_only is true.
+fn select_instances(hosts: Vec<ServiceInstance>, healthy_only: bool) -> Vec<ServiceInstance> {
+    hosts
+        .into_iter()
+        .filter(|instance| {
+            instance.enabled && instance.weight > 0.0 && (!healthy_only || instance.healthy)
+        })
+        .collect()
+}
+
+/// Deal with the service change pushes and redo in the remote client loop.
+struct NamingRemoteHandler {
+    naming_worker: NamingWorker,
+    redo_service: NamingRedoService,
+    namespace: String,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ServerRequestHandler for NamingRemoteHandler {
+    async fn handle_server_request(&mut self, conn: &mut Connection, payload_inner: PayloadInner) {
+        if TYPE_NOTIFY_SUBSCRIBER_SERVER_REQUEST.eq(&payload_inner.type_url) {
+            let server_req =
+                match NotifySubscriberServerRequest::try_from(payload_inner.body_str.as_str()) {
+                    Ok(server_req) => server_req.headers(payload_inner.headers),
+                    Err(err) => {
+                        // no request id to reply, the server pushes it again.
+                        tracing::warn!("skip the malformed NotifySubscriberRequest, {:?}", err);
+                        return;
+                    }
+                };
+            let server_req_id = server_req.get_request_id().clone();
+            let grouped_name = server_req.serviceInfo.get_grouped_service_name();
+            tracing::info!(
+                "receiver service change, service={},hosts={}",
+                &grouped_name,
+                server_req.serviceInfo.hosts.len()
+            );
+            // reply NotifySubscriberClientResponse for NotifySubscriberServerRequest
+            conn.reply_client_resp(NotifySubscriberClientResponse::new(server_req_id))
+                .await;
+            self.naming_worker
+                .update_service_info(&grouped_name, server_req.serviceInfo);
+        } else {
+            tracing::warn!(
+                "unknown receive type_url={}, maybe sdk have to upgrade!",
+                &payload_inner.type_url
+            );
+        }
+    }
+
+    /// Redo all once reconnected, retry the failed ones periodically.
+    async fn redo(&mut self, conn: &mut Connection, reconnected: bool) {
+        if reconnected {
+            tracing::info!("reconnected, redo naming registrations and subscriptions");
+            self.redo_service.on_reconnected();
+        }
+        NacosNamingService::redo(
+            &self.redo_service,
+            &mut self.naming_worker,
+            conn,
+            &self.namespace,
+        )
+        .await
+    }
+}
+
+#[async_trait::async_trait]
+impl NamingService for NacosNamingService {
+    async fn register_instance(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        service_instance: ServiceInstance,
+    ) -> Result<()> {
+        let group_name =
+            group_name.unwrap_or_else(|| crate::api::constants::DEFAULT_GROUP.to_string());
+        Self::request_instance(
+            &mut self.connection,
+            self.client_config.namespace.clone(),
+            REGISTER_INSTANCE,
+            service_name.clone(),
+            group_name.clone(),
+            service_instance.clone(),
+        )
+        .await?;
+        self.redo_service
+            .instance_registered(service_name, group_name, service_instance);
+        Ok(())
+    }
+
+    async fn deregister_instance(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        service_instance: ServiceInstance,
+    ) -> Result<()> {
+        let group_name =
+            group_name.unwrap_or_else(|| crate::api::constants::DEFAULT_GROUP.to_string());
+        // no longer redo it, even though deregister failed.
+        self.redo_service
+            .instance_deregistered(&service_name, &group_name, &service_instance);
+        Self::request_instance(
+            &mut self.connection,
+            self.client_config.namespace.clone(),
+            DE_REGISTER_INSTANCE,
+            service_name,
+            group_name,
+            service_instance,
+        )
+        .await
+    }
+
+    async fn get_all_instances(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+    ) -> Result<Vec<ServiceInstance>> {
+        let service_info = self
+            .get_service_info(service_name, group_name, &clusters)
+            .await?;
+        Ok(service_info.hosts_of_clusters(&clusters))
+    }
+
+    async fn select_instances(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+        healthy_only: bool,
+    ) -> Result<Vec<ServiceInstance>> {
+        let hosts = self
+            .get_all_instances(service_name, group_name, clusters)
+            .await?;
+        Ok(select_instances(hosts, healthy_only))
+    }
+
+    async fn select_one_healthy_instance(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+    ) -> Result<ServiceInstance> {
+        self.select_instance(service_name, group_name, clusters, &WeightedRandomSelector)
+            .await
+    }
+
+    async fn select_instance(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+        selector: &dyn Selector,
+    ) -> Result<ServiceInstance> {
+        let mut service_info = self
+            .get_service_info(service_name.clone(), group_name, &clusters)
+            .await?;
+        service_info.hosts = select_instances(service_info.hosts_of_clusters(&clusters), true);
+        selector
+            .select(&service_info)
+            .ok_or(Error::NoAvailableInstance(service_name))
+    }
+
+    async fn subscribe(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+        listener: Box<InstancesChangeListener>,
+    ) -> Result<SubscriptionId> {
+        self.do_subscribe(
+            service_name,
+            group_name,
+            clusters,
+            InstancesListener::Instances(listener),
+        )
+        .await
+    }
+
+    async fn subscribe_change_event(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        clusters: Vec<String>,
+        listener: Box<InstancesChangeEventListener>,
+    ) -> Result<SubscriptionId> {
+        self.do_subscribe(
+            service_name,
+            group_name,
+            clusters,
+            InstancesListener::ChangeEvent(listener),
+        )
+        .await
+    }
+
+    async fn unsubscribe(
+        &mut self,
+        service_name: String,
+        group_name: Option<String>,
+        subscription_id: SubscriptionId,
+    ) -> Result<()> {
+        let group_name =
+            group_name.unwrap_or_else(|| crate::api::constants::DEFAULT_GROUP.to_string());
+        let grouped_name = grouped_service_name(&service_name, &group_name);
+        let no_listener = self
+            .naming_worker
+            .remove_listener(&grouped_name, subscription_id);
+        if !no_listener {
+            return Ok(());
+        }
+        // no listener left, tell server stop pushing.
+        self.redo_service
+            .service_unsubscribed(&service_name, &group_name);
+        Self::subscribe_service(
+            &mut self.connection,
+            self.client_config.namespace.clone(),
+            service_name,
+            group_name,
+            false,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::client_config::ClientConfig;
+    use crate::api::naming::{NamingService, ServiceInstance};
+    use crate::naming::NacosNamingService;
+    use std::time::Duration;
+    use tokio::time::sleep;
+
+    #[test]
+    fn test_select_instances() {
+        use crate::naming::select_instances;
+        let mut unhealthy = ServiceInstance::new("127.0.0.1", 8080);
+        unhealthy.healthy = false;
+        let mut disabled = ServiceInstance::new("127.0.0.2", 8080);
+        disabled.enabled = false;
+        let mut zero_weight = ServiceInstance::new("127.0.0.3", 8080);
+        zero_weight.weight = 0.0;
+        let healthy = ServiceInstance::new("127.0.0.4", 8080);
+        let hosts = vec![unhealthy, disabled, zero_weight, healthy];
+
+        assert_eq!(2, select_instances(hosts.clone(), false).len());
+        let healthy_hosts = select_instances(hosts, true);
+        assert_eq!(1, healthy_hosts.len());
+        assert_eq!("127.0.0.4:8080", healthy_hosts[0].ip_and_port());
+    }
+
+    #[test]
+    fn test_instance_client_request_serialize() {
+        use crate::naming::client_request::{InstanceClientRequest, REGISTER_INSTANCE};
+        let req = InstanceClientRequest::new(
+            REGISTER_INSTANCE,
+            "ns".to_string(),
+            "test-service".to_string(),
+            "DEFAULT_GROUP".to_string(),
+            ServiceInstance::new("127.0.0.1", 8080),
+        );
+        let json: serde_json::Value = serde_json::to_value(&req).unwrap();
+        assert_eq!("registerInstance", json["type"]);
+        assert_eq!("naming", json["module"]);
+        assert_eq!("test-service", json["serviceName"]);
+        assert_eq!("127.0.0.1", json["instance"]["ip"]);
+        assert_eq!(8080, json["instance"]["port"]);
+        assert_eq!("DEFAULT", json["instance"]["clusterName"]);
+    }
+
+    // #[tokio::test]
+    async fn test_naming_service() {
+        tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .init();
+        let mut naming_service = NacosNamingService::new(
+            ClientConfig::new()
+                .server_addr("0.0.0.0:9848".to_string())
+                .app_name("test-app-name"),
+        );
+        naming_service.start().await;
+        let register = naming_service
+            .register_instance(
+                "test-service".to_string(),
+                None,
+                ServiceInstance::new("127.0.0.1", 8080),
+            )
+            .await;
+        match register {
+            Ok(_) => tracing::info!("registered the instance"),
+            Err(err) => tracing::error!("register instance error {:?}", err),
+        }
+
+        sleep(Duration::from_secs(30)).await;
+    }
+}
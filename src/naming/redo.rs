@@ -0,0 +1,223 @@
+use crate::api::naming::{grouped_service_name, ServiceInstance};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Record the registered instances and subscribed services,
+/// which are redone after reconnect until they succeed.
+#[derive(Clone)]
+pub(crate) struct NamingRedoService {
+    /// grouped_service_name@@ip:port -> registered instance
+    instances: Arc<Mutex<HashMap<String, RedoData<InstanceRedo>>>>,
+    /// grouped_service_name -> subscribed service
+    subscribers: Arc<Mutex<HashMap<String, RedoData<SubscriberRedo>>>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct InstanceRedo {
+    pub(crate) service_name: String,
+    pub(crate) group_name: String,
+    pub(crate) instance: ServiceInstance,
+}
+
+#[derive(Clone)]
+pub(crate) struct SubscriberRedo {
+    pub(crate) service_name: String,
+    pub(crate) group_name: String,
+}
+
+/// The data, and whether it is done on the current connection.
+#[derive(Clone)]
+struct RedoData<T> {
+    data: T,
+    done: bool,
+}
+
+impl NamingRedoService {
+    pub(crate) fn new() -> Self {
+        Self {
+            instances: Arc::new(Mutex::new(HashMap::new())),
+            subscribers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Record the instance registered successfully.
+    pub(crate) fn instance_registered(
+        &self,
+        service_name: String,
+        group_name: String,
+        instance: ServiceInstance,
+    ) {
+        let key = instance_key(&service_name, &group_name, &instance);
+        let data = InstanceRedo {
+            service_name,
+            group_name,
+            instance,
+        };
+        put(&self.instances, key, data);
+    }
+
+    /// Forget the instance, no longer redo it.
+    pub(crate) fn instance_deregistered(
+        &self,
+        service_name: &str,
+        group_name: &str,
+        instance: &ServiceInstance,
+    ) {
+        remove(
+            &self.instances,
+            &instance_key(service_name, group_name, instance),
+        );
+    }
+
+    /// Record the service subscribed successfully.
+    pub(crate) fn service_subscribed(&self, service_name: String, group_name: String) {
+        let key = grouped_service_name(&service_name, &group_name);
+        let data = SubscriberRedo {
+            service_name,
+            group_name,
+        };
+        put(&self.subscribers, key, data);
+    }
+
+    /// Forget the service, no longer redo it.
+    pub(crate) fn service_unsubscribed(&self, service_name: &str, group_name: &str) {
+        remove(
+            &self.subscribers,
+            &grouped_service_name(service_name, group_name),
+        );
+    }
+
+    /// Mark all as not done, called once reconnected.
+    pub(crate) fn on_reconnected(&self) {
+        mark_all_undone(&self.instances);
+        mark_all_undone(&self.subscribers);
+    }
+
+    /// The instances need to register again.
+    pub(crate) fn undone_instances(&self) -> Vec<InstanceRedo> {
+        undone(&self.instances)
+    }
+
+    /// The services need to subscribe again.
+    pub(crate) fn undone_subscribers(&self) -> Vec<SubscriberRedo> {
+        undone(&self.subscribers)
+    }
+
+    /// Mark the instance registered again.
+    pub(crate) fn instance_redone(&self, redo: &InstanceRedo) {
+        mark_done(
+            &self.instances,
+            &instance_key(&redo.service_name, &redo.group_name, &redo.instance),
+        );
+    }
+
+    /// Mark the service subscribed again.
+    pub(crate) fn subscriber_redone(&self, redo: &SubscriberRedo) {
+        mark_done(
+            &self.subscribers,
+            &grouped_service_name(&redo.service_name, &redo.group_name),
+        );
+    }
+}
+
+fn instance_key(service_name: &str, group_name: &str, instance: &ServiceInstance) -> String {
+    format!(
+        "{}{}{}",
+        grouped_service_name(service_name, group_name),
+        crate::api::constants::SERVICE_INFO_SPLITER,
+        instance.ip_and_port()
+    )
+}
+
+fn put<T>(redo_map: &Mutex<HashMap<String, RedoData<T>>>, key: String, data: T) {
+    loop {
+        let redo_lock = redo_map.try_lock();
+        if let Ok(mut mutex) = redo_lock {
+            mutex.insert(key, RedoData { data, done: true });
+            break;
+        }
+    }
+}
+
+fn remove<T>(redo_map: &Mutex<HashMap<String, RedoData<T>>>, key: &String) {
+    loop {
+        let redo_lock = redo_map.try_lock();
+        if let Ok(mut mutex) = redo_lock {
+            mutex.remove(key);
+            break;
+        }
+    }
+}
+
+fn mark_all_undone<T>(redo_map: &Mutex<HashMap<String, RedoData<T>>>) {
+    loop {
+        let redo_lock = redo_map.try_lock();
+        if let Ok(mut mutex) = redo_lock {
+            mutex.values_mut().for_each(|r| r.done = false);
+            break;
+        }
+    }
+}
+
+fn mark_done<T>(redo_map: &Mutex<HashMap<String, RedoData<T>>>, key: &String) {
+    loop {
+        let redo_lock = redo_map.try_lock();
+        if let Ok(mut mutex) = redo_lock {
+            if let Some(r) = mutex.get_mut(key) {
+                r.done = true;
+            }
+            break;
+        }
+    }
+}
+
+fn undone<T: Clone>(redo_map: &Mutex<HashMap<String, RedoData<T>>>) -> Vec<T> {
+    loop {
+        let redo_lock = redo_map.try_lock();
+        if let Ok(mutex) = redo_lock {
+            return mutex
+                .values()
+                .filter(|r| !r.done)
+                .map(|r| r.data.clone())
+                .collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::naming::ServiceInstance;
+    use crate::naming::redo::NamingRedoService;
+
+    #[test]
+    fn test_redo_after_reconnected() {
+        let redo_service = NamingRedoService::new();
+        let instance = ServiceInstance::new("127.0.0.1", 8080);
+        redo_service.instance_registered("s".to_string(), "g".to_string(), instance.clone());
+        redo_service.instance_registered(
+            "s".to_string(),
+            "g".to_string(),
+            ServiceInstance::new("127.0.0.2", 8080),
+        );
+        redo_service.service_subscribed("s".to_string(), "g".to_string());
+        assert!(redo_service.undone_instances().is_empty());
+        assert!(redo_service.undone_subscribers().is_empty());
+
+        redo_service.instance_deregistered("s", "g", &instance);
+        redo_service.on_reconnected();
+        let undone_instances = redo_service.undone_instances();
+        assert_eq!(1, undone_instances.len());
+        assert_eq!("127.0.0.2:8080", undone_instances[0].instance.ip_and_port());
+        let undone_subscribers = redo_service.undone_subscribers();
+        assert_eq!(1, undone_subscribers.len());
+
+        redo_service.instance_redone(&undone_instances[0]);
+        redo_service.subscriber_redone(&undone_subscribers[0]);
+        assert!(redo_service.undone_instances().is_empty());
+        assert!(redo_service.undone_subscribers().is_empty());
+
+        redo_service.service_unsubscribed("s", "g");
+        redo_service.on_reconnected();
+        assert!(redo_service.undone_subscribers().is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+#![allow(non_snake_case)]
+use crate::api::naming::ServiceInfo;
+use crate::common::remote::request::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct NotifySubscriberServerRequest {
+    requestId: String,
+    /// count be empty.
+    headers: HashMap<String, String>,
+    pub(crate) namespace: Option<String>,
+    pub(crate) serviceName: Option<String>,
+    pub(crate) groupName: Option<String>,
+    /// the newest service info with instances.
+    #[serde(default)]
+    pub(crate) serviceInfo: ServiceInfo,
+}
+
+impl Request for NotifySubscriberServerRequest {
+    fn get_request_id(&self) -> &String {
+        &self.requestId
+    }
+    fn get_headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+    fn headers_mut(&mut self) -> &mut HashMap<String, String> {
+        &mut self.headers
+    }
+    fn get_type_url(&self) -> &String {
+        &TYPE_NOTIFY_SUBSCRIBER_SERVER_REQUEST
+    }
+}
+
+impl NotifySubscriberServerRequest {
+    /// Sets the headers.
+    pub fn headers(self, headers: HashMap<String, String>) -> Self {
+        NotifySubscriberServerRequest { headers, ..self }
+    }
+}
+
+impl TryFrom<&str> for NotifySubscriberServerRequest {
+    type Error = crate::api::error::Error;
+
+    fn try_from(json_str: &str) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(json_str)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::remote::request::Request;
+    use crate::naming::server_request::NotifySubscriberServerRequest;
+
+    #[test]
+    fn test_notify_subscriber_server_request() {
+        let server_req = NotifySubscriberServerRequest::try_from(
+            r#"{"requestId":"1","headers":{},"serviceInfo":{"name":"s1","groupName":"g1","hosts":[]}}"#,
+        )
+        .unwrap();
+        assert_eq!("1", server_req.get_request_id());
+        assert_eq!("s1", server_req.serviceInfo.name);
+
+        assert!(matches!(
+            NotifySubscriberServerRequest::try_from(r#"{"requestId":1}"#),
+            Err(crate::api::error::Error::Serialization(_))
+        ));
+    }
+}
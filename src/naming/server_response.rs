@@ -0,0 +1,188 @@
+#![allow(non_snake_case)]
+use crate::api::naming::ServiceInfo;
+use crate::common::remote::response::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct InstanceServerResponse {
+    requestId: Option<String>,
+    resultCode: ResponseCode,
+    errorCode: u32,
+    message: Option<String>,
+    /// registerInstance or deregisterInstance
+    r#type: Option<String>,
+}
+
+impl Response for InstanceServerResponse {
+    fn is_success(&self) -> bool {
+        ResponseCode::Ok == self.resultCode
+    }
+
+    fn get_request_id(&self) -> Option<&String> {
+        Option::from(&self.requestId)
+    }
+
+    fn get_message(&self) -> Option<&String> {
+        Option::from(&self.message)
+    }
+
+    fn get_error_code(&self) -> u32 {
+        self.errorCode
+    }
+
+    fn get_type_url(&self) -> &String {
+        &TYPE_INSTANCE_SERVER_RESPONSE
+    }
+}
+
+impl TryFrom<&str> for InstanceServerResponse {
+    type Error = crate::api::error::Error;
+
+    fn try_from(json_str: &str) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(json_str)?)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct ServiceQueryServerResponse {
+    requestId: Option<String>,
+    resultCode: ResponseCode,
+    errorCode: u32,
+    message: Option<String>,
+    #[serde(default)]
+    serviceInfo: ServiceInfo,
+}
+
+impl Response for ServiceQueryServerResponse {
+    fn is_success(&self) -> bool {
+        ResponseCode::Ok == self.resultCode
+    }
+
+    fn get_request_id(&self) -> Option<&String> {
+        Option::from(&self.requestId)
+    }
+
+    fn get_message(&self) -> Option<&String> {
+        Option::from(&self.message)
+    }
+
+    fn get_error_code(&self) -> u32 {
+        self.errorCode
+    }
+
+    fn get_type_url(&self) -> &String {
+        &TYPE_SERVICE_QUERY_SERVER_RESPONSE
+    }
+}
+
+impl ServiceQueryServerResponse {
+    pub fn service_info(self) -> ServiceInfo {
+        self.serviceInfo
+    }
+}
+
+impl TryFrom<&str> for ServiceQueryServerResponse {
+    type Error = crate::api::error::Error;
+
+    fn try_from(json_str: &str) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(json_str)?)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct SubscribeServiceServerResponse {
+    requestId: Option<String>,
+    resultCode: ResponseCode,
+    errorCode: u32,
+    message: Option<String>,
+    #[serde(default)]
+    serviceInfo: ServiceInfo,
+}
+
+impl Response for SubscribeServiceServerResponse {
+    fn is_success(&self) -> bool {
+        ResponseCode::Ok == self.resultCode
+    }
+
+    fn get_request_id(&self) -> Option<&String> {
+        Option::from(&self.requestId)
+    }
+
+    fn get_message(&self) -> Option<&String> {
+        Option::from(&self.message)
+    }
+
+    fn get_error_code(&self) -> u32 {
+        self.errorCode
+    }
+
+    fn get_type_url(&self) -> &String {
+        &TYPE_SUBSCRIBE_SERVICE_SERVER_RESPONSE
+    }
+}
+
+impl SubscribeServiceServerResponse {
+    pub fn service_info(self) -> ServiceInfo {
+        self.serviceInfo
+    }
+}
+
+impl TryFrom<&str> for SubscribeServiceServerResponse {
+    type Error = crate::api::error::Error;
+
+    fn try_from(json_str: &str) -> Result<Self, Self::Error> {
+        Ok(serde_json::from_str(json_str)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::remote::response::Response;
+    use crate::naming::server_response::{
+        InstanceServerResponse, ServiceQueryServerResponse, SubscribeServiceServerResponse,
+    };
+
+    #[test]
+    fn test_instance_server_response() {
+        let ok = InstanceServerResponse::try_from(
+            r#"{"resultCode":200,"errorCode":0,"type":"registerInstance"}"#,
+        )
+        .unwrap();
+        assert!(ok.is_success());
+
+        assert!(matches!(
+            InstanceServerResponse::try_from("<html>bad gateway</html>"),
+            Err(crate::api::error::Error::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_service_query_server_response() {
+        let ok = ServiceQueryServerResponse::try_from(
+            r#"{"resultCode":200,"errorCode":0,"serviceInfo":{"name":"s1","groupName":"g1","hosts":[]}}"#,
+        )
+        .unwrap();
+        assert!(ok.is_success());
+        assert_eq!("s1", ok.service_info().name);
+
+        assert!(matches!(
+            ServiceQueryServerResponse::try_from(r#"{"resultCode":200,"serviceInfo":"#),
+            Err(crate::api::error::Error::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_subscribe_service_server_response() {
+        let ok = SubscribeServiceServerResponse::try_from(
+            r#"{"resultCode":200,"errorCode":0,"serviceInfo":{"name":"s1","groupName":"g1","hosts":[]}}"#,
+        )
+        .unwrap();
+        assert!(ok.is_success());
+        assert_eq!("g1", ok.service_info().group_name);
+
+        assert!(matches!(
+            SubscribeServiceServerResponse::try_from(""),
+            Err(crate::api::error::Error::Serialization(_))
+        ));
+    }
+}
@@ -0,0 +1,341 @@
+use crate::api::naming::{
+    InstancesChangeEvent, InstancesChangeEventListener, InstancesChangeListener, ServiceInfo,
+    ServiceInstance, SubscriptionId,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The id generator of service subscription.
+static SUBSCRIPTION_ID_SEQUENCE: AtomicU64 = AtomicU64::new(1);
+
+/// The kinds of instances change listener.
+pub(crate) enum InstancesListener {
+    /// Receive the newest instances.
+    Instances(Box<InstancesChangeListener>),
+    /// Receive the InstancesChangeEvent with added, removed and modified instances.
+    ChangeEvent(Box<InstancesChangeEventListener>),
+}
+
+#[derive(Clone)]
+pub(crate) struct NamingWorker {
+    /// grouped_service_name -> subscribed service data
+    service_data_map: Arc<Mutex<HashMap<String, ServiceData>>>,
+}
+
+impl NamingWorker {
+    pub(crate) fn new() -> Self {
+        Self {
+            service_data_map: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Add listener of subscribed service, return the id of subscription.
+    pub(crate) fn add_listener(
+        &mut self,
+        grouped_service_name: String,
+        clusters: Vec<String>,
+        listener: InstancesListener,
+    ) -> SubscriptionId {
+        let subscription_id =
+            SubscriptionId(SUBSCRIPTION_ID_SEQUENCE.fetch_add(1, Ordering::Relaxed));
+        loop {
+            let service_lock = self.service_data_map.try_lock();
+            if let Ok(mut mutex) = service_lock {
+                mutex
+                    .entry(grouped_service_name.clone())
+                    .or_insert_with(|| ServiceData::new(grouped_service_name))
+                    .listeners
+                    .push((subscription_id, clusters, listener));
+                break;
+            }
+        }
+        subscription_id
+    }
+
+    /// Remove listener, return true if no listener left and the service data was removed.
+    pub(crate) fn remove_listener(
+        &mut self,
+        grouped_service_name: &String,
+        subscription_id: SubscriptionId,
+    ) -> bool {
+        loop {
+            let service_lock = self.service_data_map.try_lock();
+            if let Ok(mut mutex) = service_lock {
+                let no_listener = match mutex.get_mut(grouped_service_name) {
+                    None => return false,
+                    Some(s) => {
+                        s.listeners.retain(|(id, _, _)| *id != subscription_id);
+                        s.listeners.is_empty()
+                    }
+                };
+                if no_listener {
+                    mutex.remove(grouped_service_name);
+                }
+                return no_listener;
+            }
+        }
+    }
+
+    /// The cached service info of subscribed service.
+    pub(crate) fn get_service_info(&self, grouped_service_name: &String) -> Option<ServiceInfo> {
+        loop {
+            let service_lock = self.service_data_map.try_lock();
+            if let Ok(mutex) = service_lock {
+                return mutex
+                    .get(grouped_service_name)
+                    .and_then(|s| s.service_info.clone());
+            }
+        }
+    }
+
+    /// Update the cached service info of subscribed service, notify all listeners
+    /// if instances changed and return true. Outdated service info is ignored.
+    pub(crate) fn update_service_info(
+        &mut self,
+        grouped_service_name: &String,
+        service_info: ServiceInfo,
+    ) -> bool {
+        loop {
+            let service_lock = self.service_data_map.try_lock();
+            if let Ok(mut mutex) = service_lock {
+                return match mutex.get_mut(grouped_service_name) {
+                    None => false,
+                    Some(s) => s.update_service_info(service_info),
+                };
+            }
+        }
+    }
+
+    /// Notify the listener with the cached instances.
+    pub(crate) fn notify_listener(
+        &self,
+        grouped_service_name: &String,
+        subscription_id: SubscriptionId,
+    ) {
+        loop {
+            let service_lock = self.service_data_map.try_lock();
+            if let Ok(mutex) = service_lock {
+                if let Some(s) = mutex.get(grouped_service_name) {
+                    s.notify_listener(&[], |id| *id == subscription_id);
+                }
+                break;
+            }
+        }
+    }
+}
+
+/// Subscribed service, with the cached service info and its listeners.
+struct ServiceData {
+    service_name: String,
+    group_name: String,
+    service_info: Option<ServiceInfo>,
+    /// who subscribe the service, with the clusters they care about.
+    listeners: Vec<(SubscriptionId, Vec<String>, InstancesListener)>,
+}
+
+impl ServiceData {
+    fn new(grouped_service_name: String) -> Self {
+        let (group_name, service_name) = grouped_service_name
+            .split_once(crate::api::constants::SERVICE_INFO_SPLITER)
+            .unwrap_or(("", grouped_service_name.as_str()));
+        Self {
+            service_name: service_name.to_string(),
+            group_name: group_name.to_string(),
+            service_info: None,
+            listeners: Vec::new(),
+        }
+    }
+
+    /// Update with the service info from server, notify listeners if instances changed.
+    fn update_service_info(&mut self, service_info: ServiceInfo) -> bool {
+        if let Some(old) = &self.service_info {
+            if service_info.last_ref_time < old.last_ref_time {
+                tracing::warn!(
+                    "outdated service info of {}, ignore it",
+                    old.get_grouped_service_name()
+                );
+                return false;
+            }
+            if sorted_hosts(&old.hosts) == sorted_hosts(&service_info.hosts) {
+                self.service_info = Some(service_info);
+                return false;
+            }
+        }
+        let old_hosts = self
+            .service_info
+            .replace(service_info)
+            .map(|old| old.hosts)
+            .unwrap_or_default();
+        self.notify_listener(&old_hosts, |_| true);
+        true
+    }
+
+    /// Notify the matched listeners with the instances in their clusters,
+    /// the old_hosts is used to build InstancesChangeEvent, which is skipped if nothing changed.
+    fn notify_listener(
+        &self,
+        old_hosts: &[ServiceInstance],
+        matches: impl Fn(&SubscriptionId) -> bool,
+    ) {
+        let service_info = match &self.service_info {
+            None => return,
+            Some(service_info) => service_info,
+        };
+        for (_, clusters, listener) in self.listeners.iter().filter(|(id, _, _)| matches(id)) {
+            let hosts = service_info.hosts_of_clusters(clusters);
+            match listener {
+                InstancesListener::Instances(listen) => (listen)(hosts),
+                InstancesListener::ChangeEvent(listen) => {
+                    let old_hosts = old_hosts
+                        .iter()
+                        .filter(|instance| {
+                            clusters.is_empty() || clusters.contains(&instance.cluster_name)
+                        })
+                        .cloned()
+                        .collect();
+                    let event = self.build_change_event(old_hosts, hosts);
+                    if !event.is_empty() {
+                        (listen)(event)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Diff the old and new instances by ip:port.
+    fn build_change_event(
+        &self,
+        old_hosts: Vec<ServiceInstance>,
+        new_hosts: Vec<ServiceInstance>,
+    ) -> InstancesChangeEvent {
+        let mut old_map: BTreeMap<String, ServiceInstance> = old_hosts
+            .into_iter()
+            .map(|instance| (instance.ip_and_port(), instance))
+            .collect();
+        let mut added_instances = Vec::new();
+        let mut modified_instances = Vec::new();
+        for instance in new_hosts.iter() {
+            match old_map.remove(&instance.ip_and_port()) {
+                None => added_instances.push(instance.clone()),
+                Some(old) if old != *instance => modified_instances.push(instance.clone()),
+                _ => {}
+            }
+        }
+        InstancesChangeEvent::new(
+            self.service_name.clone(),
+            self.group_name.clone(),
+            new_hosts,
+            added_instances,
+            old_map.into_values().collect(),
+            modified_instances,
+        )
+    }
+}
+
+fn sorted_hosts(hosts: &[ServiceInstance]) -> Vec<&ServiceInstance> {
+    let mut hosts: Vec<&ServiceInstance> = hosts.iter().collect();
+    hosts.sort_by_key(|instance| instance.ip_and_port());
+    hosts
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::naming::{ServiceInfo, ServiceInstance};
+    use crate::naming::worker::{InstancesListener, NamingWorker};
+    use std::sync::{Arc, Mutex};
+
+    fn service_info(last_ref_time: i64, hosts: Vec<ServiceInstance>) -> ServiceInfo {
+        ServiceInfo {
+            name: "test-service".to_string(),
+            group_name: "DEFAULT_GROUP".to_string(),
+            last_ref_time,
+            hosts,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_update_service_info() {
+        let mut naming_worker = NamingWorker::new();
+        let key = "DEFAULT_GROUP@@test-service".to_string();
+        // not subscribed, ignore it.
+        assert!(!naming_worker.update_service_info(&key, service_info(1, Vec::new())));
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_clone = received.clone();
+        let mut other = ServiceInstance::new("127.0.0.2", 8080);
+        other.cluster_name = "OTHER".to_string();
+        let subscription_id = naming_worker.add_listener(
+            key.clone(),
+            vec!["DEFAULT".to_string()],
+            InstancesListener::Instances(Box::new(move |hosts| {
+                received_clone.lock().unwrap().push(hosts)
+            })),
+        );
+        let hosts = vec![ServiceInstance::new("127.0.0.1", 8080), other];
+        assert!(naming_worker.update_service_info(&key, service_info(2, hosts.clone())));
+        // instances not changed.
+        let reversed = hosts.iter().rev().cloned().collect();
+        assert!(!naming_worker.update_service_info(&key, service_info(3, reversed)));
+        // outdated.
+        assert!(!naming_worker.update_service_info(&key, service_info(1, Vec::new())));
+        assert_eq!(
+            3,
+            naming_worker.get_service_info(&key).unwrap().last_ref_time
+        );
+
+        naming_worker.notify_listener(&key, subscription_id);
+        {
+            let received = received.lock().unwrap();
+            assert_eq!(2, received.len());
+            assert_eq!(1, received[0].len());
+            assert_eq!("127.0.0.1:8080", received[0][0].ip_and_port());
+        }
+
+        assert!(naming_worker.remove_listener(&key, subscription_id));
+        assert!(naming_worker.get_service_info(&key).is_none());
+    }
+
+    #[test]
+    fn test_instances_change_event() {
+        let mut naming_worker = NamingWorker::new();
+        let key = "DEFAULT_GROUP@@test-service".to_string();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        naming_worker.add_listener(
+            key.clone(),
+            Vec::new(),
+            InstancesListener::ChangeEvent(Box::new(move |event| {
+                events_clone.lock().unwrap().push(event)
+            })),
+        );
+        let kept = ServiceInstance::new("127.0.0.1", 8080);
+        let removed = ServiceInstance::new("127.0.0.2", 8080);
+        let mut modified = ServiceInstance::new("127.0.0.3", 8080);
+        naming_worker.update_service_info(
+            &key,
+            service_info(1, vec![kept.clone(), removed, modified.clone()]),
+        );
+        modified.weight = 2.0;
+        let added = ServiceInstance::new("127.0.0.4", 8080);
+        naming_worker.update_service_info(&key, service_info(2, vec![kept, modified, added]));
+
+        let events = events.lock().unwrap();
+        assert_eq!(2, events.len());
+        assert_eq!(3, events[0].get_added_instances().len());
+        assert_eq!("test-service", events[1].get_service_name());
+        assert_eq!("DEFAULT_GROUP", events[1].get_group_name());
+        assert_eq!(3, events[1].get_instances().len());
+        assert_eq!(
+            "127.0.0.4:8080",
+            events[1].get_added_instances()[0].ip_and_port()
+        );
+        assert_eq!(
+            "127.0.0.2:8080",
+            events[1].get_removed_instances()[0].ip_and_port()
+        );
+        assert_eq!(1, events[1].get_modified_instances().len());
+        assert_eq!(2.0, events[1].get_modified_instances()[0].weight);
+    }
+}
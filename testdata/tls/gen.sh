@@ -0,0 +1,21 @@
+#!/bin/sh
+# Regenerates the self-signed CA and the server cert for `nacos.test`,
+# which the TLS tests connect to a local server with.
+set -e
+cd "$(dirname "$0")"
+
+openssl req -x509 -newkey rsa:2048 -nodes -days 36500 \
+    -subj "/CN=nacos-test-ca" \
+    -addext "basicConstraints=critical,CA:TRUE" \
+    -addext "keyUsage=critical,keyCertSign,cRLSign" \
+    -keyout ca.key -out ca.pem
+
+openssl req -newkey rsa:2048 -nodes \
+    -subj "/CN=nacos.test" \
+    -keyout server.key -out server.csr
+
+printf "subjectAltName=DNS:nacos.test\nbasicConstraints=CA:FALSE\nkeyUsage=digitalSignature,keyEncipherment\nextendedKeyUsage=serverAuth\n" > server.ext
+openssl x509 -req -days 36500 -in server.csr -CA ca.pem -CAkey ca.key -CAcreateserial \
+    -extfile server.ext -out server.pem
+
+rm -f ca.key ca.srl server.csr server.ext